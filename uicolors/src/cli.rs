@@ -1,7 +1,184 @@
-use clap::Parser;
+use crate::{
+    apca_contrast, export_palette, import_file, ColorFormat, ColorLibrary, ColorPalette,
+    CopyFormat, ExportFormat, PaletteMode, WcagResult,
+};
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Args {
-    pub color: Option<String>,
+    /// Color to open the editor with, e.g. "#3b82f6" or "oklch(0.62 0.19 260)"
+    #[arg(value_parser = parse_color)]
+    pub color: Option<ColorFormat>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands print to stdout and exit without opening a window.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the 50..950 palette generated from a color
+    Palette {
+        #[arg(value_parser = parse_color)]
+        color: ColorFormat,
+        /// json, tailwind, css, scss, tokens, swiftui, uikit or compose
+        #[arg(short, long, default_value = "json", value_parser = parse_export_format)]
+        format: ExportFormat,
+        /// hsl or oklch
+        #[arg(short, long, default_value = "hsl", value_parser = parse_palette_mode)]
+        mode: PaletteMode,
+        /// Name of the palette in the generated code
+        #[arg(short, long, default_value = "primary")]
+        name: String,
+    },
+    /// Convert a color to another color space
+    Convert {
+        #[arg(value_parser = parse_color)]
+        color: ColorFormat,
+        /// hex, rgb, hsl, oklab, oklch, lab, lch, hct or tailwind
+        #[arg(short, long, default_value = "hex")]
+        to: String,
+    },
+    /// Print the WCAG 2.1 and APCA contrast of text on a background
+    Contrast {
+        #[arg(value_parser = parse_color)]
+        fg: ColorFormat,
+        #[arg(value_parser = parse_color)]
+        bg: ColorFormat,
+    },
+    /// Import colors from tailwind.config.js, CSS custom properties or Design Tokens JSON
+    Import {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Library file to import into, instead of the one used by the app
+        #[arg(long)]
+        library: Option<PathBuf>,
+        /// Print the colors found without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+fn parse_color(s: &str) -> Result<ColorFormat, String> {
+    ColorFormat::parse(s).map_err(|err| err.to_string())
+}
+
+fn parse_export_format(s: &str) -> Result<ExportFormat, String> {
+    ExportFormat::from_name(s).ok_or_else(|| format!("unknown format \"{}\"", s))
+}
+
+fn parse_palette_mode(s: &str) -> Result<PaletteMode, String> {
+    match s.to_lowercase().as_str() {
+        "hsl" => Ok(PaletteMode::Hsl),
+        "oklch" => Ok(PaletteMode::Oklch),
+        _ => Err(format!("unknown palette mode \"{}\"", s)),
+    }
+}
+
+pub fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Palette {
+            color,
+            format,
+            mode,
+            name,
+        } => {
+            let palette = ColorPalette::from(color.canonicalize()).mode(mode);
+            let out = export_palette(&name, palette, format);
+            println!("{}", out.trim_end());
+        }
+        Command::Convert { color, to } => println!("{}", convert(color, &to)?),
+        Command::Contrast { fg, bg } => {
+            let result = WcagResult::new(fg, bg);
+            let pass = |pass: bool| if pass { "pass" } else { "fail" };
+            println!("ratio      {:.2}:1", result.ratio);
+            println!("AA         {}", pass(result.aa));
+            println!("AA large   {}", pass(result.aa_large));
+            println!("AAA        {}", pass(result.aaa));
+            println!("AAA large  {}", pass(result.aaa_large));
+            println!("APCA       Lc {:.1}", apca_contrast(fg, bg));
+        }
+        Command::Import {
+            files,
+            library,
+            dry_run,
+        } => {
+            let path = library
+                .or_else(ColorLibrary::default_path)
+                .ok_or_else(|| anyhow!("no config directory to keep the library in"))?;
+            let mut library = ColorLibrary::load(&path)?.unwrap_or_default();
+            let mut added = 0;
+            for file in files {
+                let report = import_file(&file)?;
+                for (entry, reason) in &report.skipped {
+                    eprintln!("{}: skipped {}: {}", file.display(), entry, reason);
+                }
+                for item in &report.items {
+                    let name = item.name.as_deref().unwrap_or("");
+                    let shades = match item.shades.len() {
+                        0 => "".to_owned(),
+                        n => format!(" ({} shades)", n),
+                    };
+                    println!("{:<24} {}{}", name, item.color.to_rgb(), shades);
+                }
+                added += library.import(report.items);
+            }
+            if !dry_run {
+                library.save(&path)?;
+                println!("{} colors added to {}", added, path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn convert(color: ColorFormat, to: &str) -> Result<String> {
+    if let Some(format) = CopyFormat::from_name(to) {
+        return Ok(format.format(color));
+    }
+    let s = match to.to_lowercase().as_str() {
+        "oklab" => color.to_oklab().to_string(),
+        "lab" => color.to_lab().to_string(),
+        "lch" => color.to_lch().to_string(),
+        "hct" => color.to_hct().to_string(),
+        _ => return Err(anyhow!("unknown color space \"{}\"", to)),
+    };
+    Ok(s)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn args() {
+        let args = Args::try_parse_from(["uicolors", "#3b82f6"]).unwrap();
+        assert_eq!(args.color, Some(ColorFormat::Rgb(0x3b, 0x82, 0xf6)));
+        assert!(args.command.is_none());
+
+        let args = Args::try_parse_from(["uicolors", "palette", "red", "-f", "tailwind"]).unwrap();
+        match args.command {
+            Some(Command::Palette { color, format, .. }) => {
+                assert_eq!(color, ColorFormat::Rgb(255, 0, 0));
+                assert_eq!(format, ExportFormat::Tailwind);
+            }
+            _ => panic!("expected palette command"),
+        }
+
+        assert!(Args::try_parse_from(["uicolors", "convert", "nope"]).is_err());
+        assert!(Args::try_parse_from(["uicolors", "palette", "red", "-f", "xml"]).is_err());
+    }
+
+    #[test]
+    fn convert_to() {
+        let color = ColorFormat::Rgb(0x3b, 0x82, 0xf6);
+        assert_eq!(convert(color, "hex").unwrap(), "#3b82f6");
+        assert_eq!(convert(color, "rgb").unwrap(), "rgb(59 130 246)");
+        assert!(convert(color, "oklch").unwrap().starts_with("oklch(0.623"));
+        assert_eq!(convert(color, "tailwind").unwrap(), "bg-blue-500");
+        assert!(convert(color, "xyz").is_err());
+    }
 }
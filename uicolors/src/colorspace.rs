@@ -0,0 +1,356 @@
+// Conversions between sRGB and the other color spaces that CSS Color Level 4 understands.
+//
+// All functions work on plain floats: sRGB channels are in 0..1, CIELAB L is in 0..100 and
+// OKLab L is in 0..1. Matrices are taken from the CSS Color 4 specification.
+
+pub type Vec3 = [f32; 3];
+
+type Mat3 = [[f64; 3]; 3];
+
+fn mul(m: &Mat3, v: Vec3) -> Vec3 {
+    let [x, y, z] = v.map(|c| c as f64);
+    [
+        (m[0][0] * x + m[0][1] * y + m[0][2] * z) as f32,
+        (m[1][0] * x + m[1][1] * y + m[1][2] * z) as f32,
+        (m[2][0] * x + m[2][1] * y + m[2][2] * z) as f32,
+    ]
+}
+
+#[rustfmt::skip]
+const LINEAR_SRGB_TO_XYZ_D65: Mat3 = [
+    [0.41239079926595934, 0.357584339383878, 0.1804807884018343],
+    [0.21263900587151027, 0.715168678767756, 0.07219231536073371],
+    [0.01933081871559182, 0.11919477979462598, 0.9505321522496607],
+];
+
+#[rustfmt::skip]
+const XYZ_D65_TO_LINEAR_SRGB: Mat3 = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [0.05563007969699366, -0.20397695888897652, 1.0569715142428786],
+];
+
+#[rustfmt::skip]
+const LINEAR_P3_TO_XYZ_D65: Mat3 = [
+    [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+    [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+    [0.0, 0.04511338185890264, 1.043944368900976],
+];
+
+#[rustfmt::skip]
+const XYZ_D65_TO_LINEAR_P3: Mat3 = [
+    [2.493496911941425, -0.9313836179191239, -0.40271078445071684],
+    [-0.8294889695615747, 1.7626640603183463, 0.023624685841943577],
+    [0.03584583024378447, -0.07617238926804182, 0.9568845240076872],
+];
+
+#[rustfmt::skip]
+const LINEAR_REC2020_TO_XYZ_D65: Mat3 = [
+    [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+    [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+    [0.0, 0.028072693049087428, 1.060985057710791],
+];
+
+#[rustfmt::skip]
+const XYZ_D65_TO_LINEAR_REC2020: Mat3 = [
+    [1.716651187971268, -0.355670783776392, -0.25336628137366],
+    [-0.666684351832489, 1.616481236634939, 0.0157685458139111],
+    [0.017639857445311, -0.042770613257809, 0.942103121235474],
+];
+
+// Bradford chromatic adaptation between the D50 and D65 white points
+#[rustfmt::skip]
+const XYZ_D50_TO_D65: Mat3 = [
+    [0.9554734527042182, -0.023098536874261423, 0.0632593086610217],
+    [-0.028369706963208136, 1.0099954580058226, 0.021041398966943008],
+    [0.012314001688319899, -0.020507696433477912, 1.3303659366080753],
+];
+
+#[rustfmt::skip]
+const XYZ_D65_TO_D50: Mat3 = [
+    [1.0479298208405488, 0.022946793341019088, -0.05019222954313557],
+    [0.029627815688159344, 0.990434484573249, -0.01707382502938514],
+    [-0.009243058152591178, 0.015055144896577895, 0.7518742899580008],
+];
+
+const D50_WHITE: Vec3 = [0.3457 / 0.3585, 1., (1. - 0.3457 - 0.3585) / 0.3585];
+
+pub fn srgb_to_linear(c: f32) -> f32 {
+    let abs = c.abs();
+    if abs <= 0.04045 {
+        c / 12.92
+    } else {
+        c.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+    let abs = c.abs();
+    if abs <= 0.0031308 {
+        c * 12.92
+    } else {
+        c.signum() * (1.055 * abs.powf(1. / 2.4) - 0.055)
+    }
+}
+
+// Rec. 2020 transfer function constants, written out as in CSS Color 4 even though f32 keeps fewer
+// digits
+#[allow(clippy::excessive_precision)]
+const REC2020_ALPHA: f32 = 1.09929682680944;
+#[allow(clippy::excessive_precision)]
+const REC2020_BETA: f32 = 0.018053968510807;
+
+pub fn rec2020_to_linear(c: f32) -> f32 {
+    let abs = c.abs();
+    if abs < REC2020_BETA * 4.5 {
+        c / 4.5
+    } else {
+        c.signum() * ((abs + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45)
+    }
+}
+
+pub fn linear_to_rec2020(c: f32) -> f32 {
+    let abs = c.abs();
+    if abs < REC2020_BETA {
+        c * 4.5
+    } else {
+        c.signum() * (REC2020_ALPHA * abs.powf(0.45) - (REC2020_ALPHA - 1.))
+    }
+}
+
+pub fn srgb_to_linear_srgb(rgb: Vec3) -> Vec3 {
+    rgb.map(srgb_to_linear)
+}
+
+pub fn linear_srgb_to_srgb(rgb: Vec3) -> Vec3 {
+    rgb.map(linear_to_srgb)
+}
+
+pub fn linear_srgb_to_xyz_d65(rgb: Vec3) -> Vec3 {
+    mul(&LINEAR_SRGB_TO_XYZ_D65, rgb)
+}
+
+pub fn xyz_d65_to_linear_srgb(xyz: Vec3) -> Vec3 {
+    mul(&XYZ_D65_TO_LINEAR_SRGB, xyz)
+}
+
+pub fn xyz_d50_to_d65(xyz: Vec3) -> Vec3 {
+    mul(&XYZ_D50_TO_D65, xyz)
+}
+
+pub fn xyz_d65_to_d50(xyz: Vec3) -> Vec3 {
+    mul(&XYZ_D65_TO_D50, xyz)
+}
+
+/// Gamma-encoded Display P3 to linear sRGB. Results may fall outside 0..1.
+pub fn display_p3_to_linear_srgb(rgb: Vec3) -> Vec3 {
+    let xyz = mul(&LINEAR_P3_TO_XYZ_D65, rgb.map(srgb_to_linear));
+    xyz_d65_to_linear_srgb(xyz)
+}
+
+/// Linear sRGB to gamma-encoded Display P3.
+pub fn linear_srgb_to_display_p3(rgb: Vec3) -> Vec3 {
+    let xyz = linear_srgb_to_xyz_d65(rgb);
+    mul(&XYZ_D65_TO_LINEAR_P3, xyz).map(linear_to_srgb)
+}
+
+/// Gamma-encoded Rec. 2020 to linear sRGB. Results may fall outside 0..1.
+pub fn rec2020_to_linear_srgb(rgb: Vec3) -> Vec3 {
+    let xyz = mul(&LINEAR_REC2020_TO_XYZ_D65, rgb.map(rec2020_to_linear));
+    xyz_d65_to_linear_srgb(xyz)
+}
+
+/// Linear sRGB to gamma-encoded Rec. 2020.
+pub fn linear_srgb_to_rec2020(rgb: Vec3) -> Vec3 {
+    let xyz = linear_srgb_to_xyz_d65(rgb);
+    mul(&XYZ_D65_TO_LINEAR_REC2020, xyz).map(linear_to_rec2020)
+}
+
+// The OKLab matrices keep the digits published by Björn Ottosson and CSS Color 4, so they can be
+// checked against the source
+#[allow(clippy::excessive_precision)]
+pub fn linear_srgb_to_oklab(rgb: Vec3) -> Vec3 {
+    let [r, g, b] = rgb;
+    let l = 0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b;
+    let m = 0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b;
+    let s = 0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b;
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_linear_srgb(lab: Vec3) -> Vec3 {
+    let [l, a, b] = lab;
+    let l_ = l + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = l - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = l - 0.0894841775 * a - 1.2914855480 * b;
+    let (l, m, s) = (l_.powi(3), m_.powi(3), s_.powi(3));
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+}
+
+const LAB_KAPPA: f32 = 24389. / 27.;
+const LAB_EPSILON: f32 = 216. / 24389.;
+
+/// CIELAB (D50, as CSS uses) to CIE XYZ with the D50 white point.
+pub fn lab_to_xyz_d50(lab: Vec3) -> Vec3 {
+    let [l, a, b] = lab;
+    let fy = (l + 16.) / 116.;
+    let fx = a / 500. + fy;
+    let fz = fy - b / 200.;
+    let x = if fx.powi(3) > LAB_EPSILON {
+        fx.powi(3)
+    } else {
+        (116. * fx - 16.) / LAB_KAPPA
+    };
+    let y = if l > LAB_KAPPA * LAB_EPSILON {
+        fy.powi(3)
+    } else {
+        l / LAB_KAPPA
+    };
+    let z = if fz.powi(3) > LAB_EPSILON {
+        fz.powi(3)
+    } else {
+        (116. * fz - 16.) / LAB_KAPPA
+    };
+    [x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]]
+}
+
+pub fn xyz_d50_to_lab(xyz: Vec3) -> Vec3 {
+    let f = |t: f32| {
+        if t > LAB_EPSILON {
+            t.cbrt()
+        } else {
+            (LAB_KAPPA * t + 16.) / 116.
+        }
+    };
+    let fx = f(xyz[0] / D50_WHITE[0]);
+    let fy = f(xyz[1] / D50_WHITE[1]);
+    let fz = f(xyz[2] / D50_WHITE[2]);
+    [116. * fy - 16., 500. * (fx - fy), 200. * (fy - fz)]
+}
+
+pub fn lab_to_linear_srgb(lab: Vec3) -> Vec3 {
+    xyz_d65_to_linear_srgb(xyz_d50_to_d65(lab_to_xyz_d50(lab)))
+}
+
+pub fn linear_srgb_to_lab(rgb: Vec3) -> Vec3 {
+    xyz_d50_to_lab(xyz_d65_to_d50(linear_srgb_to_xyz_d65(rgb)))
+}
+
+/// Polar form (lightness, chroma, hue in degrees) to rectangular (lightness, a, b).
+pub fn lch_to_lab(lch: Vec3) -> Vec3 {
+    let [l, c, h] = lch;
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+/// Rectangular (lightness, a, b) to polar form (lightness, chroma, hue in degrees).
+pub fn lab_to_lch(lab: Vec3) -> Vec3 {
+    let [l, a, b] = lab;
+    let c = (a * a + b * b).sqrt();
+    let h = b.atan2(a).to_degrees().rem_euclid(360.);
+    [l, c, h]
+}
+
+/// Hue (degrees), saturation and lightness (0..1) to gamma-encoded sRGB.
+pub fn hsl_to_srgb(hsl: Vec3) -> Vec3 {
+    let [h, s, l] = hsl;
+    let f = |n: f32| {
+        let k = (n + h.rem_euclid(360.) / 30.) % 12.;
+        let a = s * l.min(1. - l);
+        l - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+    };
+    [f(0.), f(8.), f(4.)]
+}
+
+/// Gamma-encoded sRGB to hue (degrees), saturation and lightness (0..1). The inverse of
+/// `hsl_to_srgb`; grays get hue 0.
+pub fn srgb_to_hsl(rgb: Vec3) -> Vec3 {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.;
+    let d = max - min;
+    if d <= 0. {
+        return [0., 0., l];
+    }
+    let s = d / (1. - (2. * l - 1.).abs());
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / d + 2.
+    } else {
+        (r - g) / d + 4.
+    };
+    [h * 60., s.min(1.), l]
+}
+
+/// Hue (degrees), whiteness and blackness (0..1) to gamma-encoded sRGB.
+pub fn hwb_to_srgb(hwb: Vec3) -> Vec3 {
+    let [h, w, b] = hwb;
+    if w + b >= 1. {
+        let gray = w / (w + b);
+        return [gray; 3];
+    }
+    hsl_to_srgb([h, 1., 0.5]).map(|c| c * (1. - w - b) + w)
+}
+
+pub fn in_srgb_gamut(rgb: Vec3) -> bool {
+    const EPS: f32 = 1e-4;
+    rgb.iter().all(|c| (-EPS..=1. + EPS).contains(c))
+}
+
+/// Highest OKLCH chroma, up to `limit`, that is inside the sRGB gamut at this lightness and hue.
+pub fn max_srgb_chroma(l: f32, h: f32, limit: f32) -> f32 {
+    let in_gamut = |c: f32| in_srgb_gamut(oklab_to_linear_srgb(lch_to_lab([l, c, h])));
+    if in_gamut(limit) {
+        return limit;
+    }
+    let (mut lo, mut hi) = (0., limit);
+    while hi - lo > 1e-4 {
+        let mid = (lo + hi) / 2.;
+        if in_gamut(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Maps an OKLCH color into the sRGB gamut by reducing its chroma at constant lightness and
+/// hue until it fits. Returns gamma-encoded sRGB.
+pub fn oklch_to_srgb_gamut_mapped(lch: Vec3) -> Vec3 {
+    let [l, c, h] = lch;
+    if l >= 1. {
+        return [1.; 3];
+    }
+    if l <= 0. {
+        return [0.; 3];
+    }
+    let to_srgb = |c: f32| linear_srgb_to_srgb(oklab_to_linear_srgb(lch_to_lab([l, c, h])));
+    let clip = |rgb: Vec3| rgb.map(|x| x.clamp(0., 1.));
+
+    let rgb = to_srgb(c);
+    if in_srgb_gamut(rgb) {
+        return clip(rgb);
+    }
+    let (mut lo, mut hi) = (0., c);
+    while hi - lo > 1e-4 {
+        let mid = (lo + hi) / 2.;
+        if in_srgb_gamut(to_srgb(mid)) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    clip(to_srgb(lo))
+}
@@ -0,0 +1,150 @@
+// Contrast between text and background colors: WCAG 2.1 contrast ratios and APCA lightness
+// contrast (Lc) values.
+
+use crate::{colorspace::*, ColorFormat};
+use gpui::{hsla, Hsla};
+
+/// WCAG 2.1 relative luminance of the color, in 0..1.
+pub fn relative_luminance(color: ColorFormat) -> f32 {
+    let [r, g, b] = color.to_srgb().map(|c| srgb_to_linear(c.clamp(0., 1.)));
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+// translucent text is seen blended over its background
+fn composite(fg: ColorFormat, bg: ColorFormat) -> ColorFormat {
+    let a = fg.alpha();
+    if a >= 1. {
+        return fg;
+    }
+    let (fg, bg) = (fg.to_srgb(), bg.to_srgb());
+    let rgb = [0, 1, 2].map(|i| fg[i] * a + bg[i] * (1. - a));
+    ColorFormat::from_srgb(rgb, 1.)
+}
+
+/// WCAG 2.1 contrast ratio of text on a background, from 1 (no contrast) to 21 (black on white).
+/// Translucent text is blended over the background first, so only opaque colors can be swapped.
+pub fn contrast_ratio(text: ColorFormat, background: ColorFormat) -> f32 {
+    let l0 = relative_luminance(composite(text, background));
+    let l1 = relative_luminance(background);
+    let (hi, lo) = if l0 > l1 { (l0, l1) } else { (l1, l0) };
+    (hi + 0.05) / (lo + 0.05)
+}
+
+/// APCA (0.0.98G) lightness contrast of text on a background. Positive for dark text on a
+/// light background, negative for light text on a dark background; roughly -108..106.
+pub fn apca_contrast(text: ColorFormat, bg: ColorFormat) -> f32 {
+    fn luminance(color: ColorFormat) -> f32 {
+        const BLACK_THRESHOLD: f32 = 0.022;
+        const BLACK_CLAMP: f32 = 1.414;
+        let [r, g, b] = color.to_srgb().map(|c| c.clamp(0., 1.).powf(2.4));
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        if y < BLACK_THRESHOLD {
+            y + (BLACK_THRESHOLD - y).powf(BLACK_CLAMP)
+        } else {
+            y
+        }
+    }
+
+    let y_text = luminance(composite(text, bg));
+    let y_bg = luminance(bg);
+    if (y_bg - y_text).abs() < 0.0005 {
+        return 0.;
+    }
+    let lc = if y_bg > y_text {
+        let sapc = (y_bg.powf(0.56) - y_text.powf(0.57)) * 1.14;
+        if sapc < 0.1 {
+            0.
+        } else {
+            sapc - 0.027
+        }
+    } else {
+        let sapc = (y_bg.powf(0.65) - y_text.powf(0.62)) * 1.14;
+        if sapc > -0.1 {
+            0.
+        } else {
+            sapc + 0.027
+        }
+    };
+    lc * 100.
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WcagResult {
+    pub ratio: f32,
+    pub aa: bool,        // normal text, 4.5:1
+    pub aa_large: bool,  // large text (18pt, or 14pt bold), 3:1
+    pub aaa: bool,       // normal text, 7:1
+    pub aaa_large: bool, // large text, 4.5:1
+}
+
+impl WcagResult {
+    pub fn new(fg: ColorFormat, bg: ColorFormat) -> Self {
+        // WCAG compares against the thresholds without rounding the ratio
+        let ratio = contrast_ratio(fg, bg);
+        Self {
+            ratio,
+            aa: ratio >= 4.5,
+            aa_large: ratio >= 3.,
+            aaa: ratio >= 7.,
+            aaa_large: ratio >= 4.5,
+        }
+    }
+
+    /// Short label for the highest level passed: "AAA", "AA", "AA18" for large text only.
+    pub fn label(&self) -> &'static str {
+        if self.aaa {
+            "AAA"
+        } else if self.aa {
+            "AA"
+        } else if self.aa_large {
+            "AA18"
+        } else {
+            ""
+        }
+    }
+}
+
+/// Black or white, whichever reads better on the given background according to APCA.
+pub fn label_color(bg: Hsla) -> Hsla {
+    let (white, black) = (hsla(0., 0., 1., 1.), hsla(0., 0., 0., 1.));
+    let bg = ColorFormat::from(bg).with_alpha(1.);
+    let on_white = apca_contrast(ColorFormat::from(white), bg).abs();
+    let on_black = apca_contrast(ColorFormat::from(black), bg).abs();
+    if on_white > on_black {
+        white
+    } else {
+        black
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wcag() {
+        let (black, white) = (ColorFormat::Rgb(0, 0, 0), ColorFormat::Rgb(255, 255, 255));
+        assert!((contrast_ratio(black, white) - 21.).abs() < 1e-3);
+        assert!((contrast_ratio(white, white) - 1.).abs() < 1e-3);
+        // #767676 is the lightest gray passing AA on white
+        let gray = ColorFormat::Rgb(0x76, 0x76, 0x76);
+        assert!((contrast_ratio(gray, white) - 4.54).abs() < 0.01);
+        assert_eq!(WcagResult::new(gray, white).label(), "AA");
+        let gray = ColorFormat::Rgb(0x77, 0x77, 0x77);
+        assert_eq!(WcagResult::new(gray, white).label(), "AA18");
+
+        let red = ColorFormat::Rgba(255, 0, 0, 128);
+        assert!((contrast_ratio(red, white) - 2.44).abs() < 0.01);
+        assert!((contrast_ratio(white, red) - 4.).abs() < 0.01);
+    }
+
+    #[test]
+    fn apca() {
+        let (black, white) = (ColorFormat::Rgb(0, 0, 0), ColorFormat::Rgb(255, 255, 255));
+        let gray = ColorFormat::Rgb(0x88, 0x88, 0x88);
+        assert!((apca_contrast(black, white) - 106.04).abs() < 0.1);
+        assert!((apca_contrast(white, black) + 107.88).abs() < 0.1);
+        assert!((apca_contrast(gray, white) - 63.06).abs() < 0.1);
+        assert!((apca_contrast(white, gray) + 68.54).abs() < 0.1);
+    }
+}
@@ -0,0 +1,741 @@
+// Parser for CSS Color Level 4 values: hex notation, named colors and the color functions.
+
+use crate::colorspace::*;
+use crate::ColorFormat;
+use std::f32::consts::PI;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseColorErrorKind {
+    Empty,
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    InvalidHex,
+    InvalidNumber,
+    UnknownName(String),
+    UnknownFunction(String),
+    MissingColorSpace,
+    UnknownColorSpace(String),
+    UnknownUnit(String),
+    InvalidComponent,
+    WrongArgCount { expected: usize, found: usize },
+    TrailingInput,
+}
+
+/// Error returned by [`ColorFormat::parse`], with the byte offset where parsing failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseColorError {
+    pub offset: usize,
+    pub kind: ParseColorErrorKind,
+}
+
+impl fmt::Display for ParseColorErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty input"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of input"),
+            Self::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            Self::InvalidHex => write!(f, "expected 3, 4, 6 or 8 hex digits"),
+            Self::InvalidNumber => write!(f, "invalid number"),
+            Self::UnknownName(s) => write!(f, "unknown color name {:?}", s),
+            Self::UnknownFunction(s) => write!(f, "unknown color function {:?}", s),
+            Self::MissingColorSpace => write!(f, "expected a color space such as srgb"),
+            Self::UnknownColorSpace(s) => write!(f, "unsupported color space {:?}", s),
+            Self::UnknownUnit(s) => write!(f, "unknown unit {:?}", s),
+            Self::InvalidComponent => write!(f, "invalid value for this component"),
+            Self::WrongArgCount { expected, found } => {
+                write!(f, "expected {} components, found {}", expected, found)
+            }
+            Self::TrailingInput => write!(f, "unexpected trailing input"),
+        }
+    }
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.offset)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+pub type ParseResult<T> = Result<T, ParseColorError>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Number(f32),
+    Percent(f32), // 0..100
+    Degrees(f32), // angles are converted to degrees
+    None,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Component {
+    offset: usize,
+    value: Value,
+}
+
+impl Component {
+    fn err(&self) -> ParseColorError {
+        ParseColorError {
+            offset: self.offset,
+            kind: ParseColorErrorKind::InvalidComponent,
+        }
+    }
+
+    // a number, or a percentage where 100% maps to `full`
+    fn number(&self, full: f32) -> ParseResult<f32> {
+        match self.value {
+            Value::Number(x) => Ok(x),
+            Value::Percent(p) => Ok(p / 100. * full),
+            Value::None => Ok(0.),
+            Value::Degrees(_) => Err(self.err()),
+        }
+    }
+
+    fn percent(&self) -> ParseResult<f32> {
+        // CSS 4 allows bare numbers where legacy syntax required percentages
+        self.number(100.)
+    }
+
+    fn hue(&self) -> ParseResult<f32> {
+        match self.value {
+            Value::Number(x) | Value::Degrees(x) => Ok(x.rem_euclid(360.)),
+            Value::None => Ok(0.),
+            Value::Percent(_) => Err(self.err()),
+        }
+    }
+
+    fn alpha(&self) -> ParseResult<f32> {
+        Ok(self.number(1.)?.clamp(0., 1.))
+    }
+}
+
+struct Args {
+    components: Vec<Component>,
+    alpha: Option<Component>,
+    offset: usize,
+}
+
+impl Args {
+    fn expect3(&self) -> ParseResult<[Component; 3]> {
+        self.components
+            .as_slice()
+            .try_into()
+            .map_err(|_| ParseColorError {
+                offset: self.offset,
+                kind: ParseColorErrorKind::WrongArgCount {
+                    expected: 3,
+                    found: self.components.len(),
+                },
+            })
+    }
+
+    fn alpha(&self) -> ParseResult<f32> {
+        match &self.alpha {
+            Some(a) => a.alpha(),
+            None => Ok(1.),
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn err<T>(&self, offset: usize, kind: ParseColorErrorKind) -> ParseResult<T> {
+        Err(ParseColorError { offset, kind })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> ParseResult<()> {
+        if self.eat(c) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(x) => self.err(self.pos, ParseColorErrorKind::UnexpectedChar(x)),
+            None => self.err(self.pos, ParseColorErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn ident(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn end(&mut self) -> ParseResult<()> {
+        self.skip_ws();
+        if self.pos < self.src.len() {
+            return self.err(self.pos, ParseColorErrorKind::TrailingInput);
+        }
+        Ok(())
+    }
+
+    fn component(&mut self) -> ParseResult<Component> {
+        self.skip_ws();
+        let offset = self.pos;
+        let rest = self.rest();
+        if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let ident = self.ident();
+            if ident.eq_ignore_ascii_case("none") {
+                return Ok(Component {
+                    offset,
+                    value: Value::None,
+                });
+            }
+            return self.err(offset, ParseColorErrorKind::InvalidComponent);
+        }
+
+        let bytes = rest.as_bytes();
+        let mut len = 0;
+        if len < bytes.len() && (bytes[len] == b'+' || bytes[len] == b'-') {
+            len += 1;
+        }
+        while len < bytes.len() && (bytes[len].is_ascii_digit() || bytes[len] == b'.') {
+            len += 1;
+        }
+        // exponent, but not the "e" of a unit such as "deg"
+        if len < bytes.len() && (bytes[len] == b'e' || bytes[len] == b'E') {
+            let mut n = len + 1;
+            if n < bytes.len() && (bytes[n] == b'+' || bytes[n] == b'-') {
+                n += 1;
+            }
+            if n < bytes.len() && bytes[n].is_ascii_digit() {
+                while n < bytes.len() && bytes[n].is_ascii_digit() {
+                    n += 1;
+                }
+                len = n;
+            }
+        }
+        if len == 0 {
+            return match self.peek() {
+                Some(c) => self.err(offset, ParseColorErrorKind::UnexpectedChar(c)),
+                None => self.err(offset, ParseColorErrorKind::UnexpectedEnd),
+            };
+        }
+        let x: f32 = match rest[..len].parse() {
+            Ok(x) => x,
+            Err(_) => return self.err(offset, ParseColorErrorKind::InvalidNumber),
+        };
+        self.pos += len;
+
+        if self.peek() == Some('%') {
+            self.pos += 1;
+            return Ok(Component {
+                offset,
+                value: Value::Percent(x),
+            });
+        }
+        let unit_offset = self.pos;
+        let unit = self.ident();
+        let value = match unit.to_ascii_lowercase().as_str() {
+            "" => Value::Number(x),
+            "deg" => Value::Degrees(x),
+            "rad" => Value::Degrees(x * 180. / PI),
+            "grad" => Value::Degrees(x * 0.9),
+            "turn" => Value::Degrees(x * 360.),
+            _ => {
+                return self.err(
+                    unit_offset,
+                    ParseColorErrorKind::UnknownUnit(unit.to_owned()),
+                )
+            }
+        };
+        Ok(Component { offset, value })
+    }
+
+    // components of a color function up to and including the closing parenthesis, in either
+    // the legacy comma separated syntax or the modern space separated syntax with "/ alpha"
+    fn args(&mut self) -> ParseResult<Args> {
+        self.skip_ws();
+        let offset = self.pos;
+        let mut components = vec![self.component()?];
+        let mut alpha = None;
+        if self.eat(',') {
+            components.push(self.component()?);
+            while self.eat(',') {
+                components.push(self.component()?);
+            }
+            if components.len() == 4 {
+                alpha = components.pop();
+            }
+        } else {
+            loop {
+                self.skip_ws();
+                match self.peek() {
+                    Some(')') | Some('/') | None => break,
+                    _ => components.push(self.component()?),
+                }
+            }
+            if self.eat('/') {
+                alpha = Some(self.component()?);
+            }
+        }
+        self.expect(')')?;
+        Ok(Args {
+            components,
+            alpha,
+            offset,
+        })
+    }
+
+    fn color(&mut self) -> ParseResult<ColorFormat> {
+        self.skip_ws();
+        let offset = self.pos;
+        let color = match self.peek() {
+            None => return self.err(offset, ParseColorErrorKind::Empty),
+            Some('#') => {
+                self.pos += 1;
+                self.hex()?
+            }
+            Some(c) if c.is_ascii_digit() => self.bare_hsl()?,
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.ident();
+                if self.eat('(') {
+                    self.function(offset, name)?
+                } else {
+                    match named_color(name) {
+                        Some(color) => color,
+                        None => {
+                            return self
+                                .err(offset, ParseColorErrorKind::UnknownName(name.to_owned()))
+                        }
+                    }
+                }
+            }
+            Some(c) => return self.err(offset, ParseColorErrorKind::UnexpectedChar(c)),
+        };
+        self.end()?;
+        Ok(color)
+    }
+
+    fn hex(&mut self) -> ParseResult<ColorFormat> {
+        let offset = self.pos;
+        let hex = self.ident();
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return self.err(offset, ParseColorErrorKind::InvalidHex);
+        }
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap();
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+        let color = match hex.len() {
+            3 => ColorFormat::Rgb(digit(0) * 17, digit(1) * 17, digit(2) * 17),
+            4 => ColorFormat::Rgba(digit(0) * 17, digit(1) * 17, digit(2) * 17, digit(3) * 17),
+            6 => ColorFormat::Rgb(byte(0), byte(2), byte(4)),
+            8 => ColorFormat::Rgba(byte(0), byte(2), byte(4), byte(6)),
+            _ => return self.err(offset, ParseColorErrorKind::InvalidHex),
+        };
+        Ok(color.opaque_if_possible())
+    }
+
+    // "h,s,l" and "h,s,l,a" as written by ColorFormat::to_string
+    fn bare_hsl(&mut self) -> ParseResult<ColorFormat> {
+        let offset = self.pos;
+        let mut parts = vec![self.component()?];
+        while self.eat(',') {
+            parts.push(self.component()?);
+        }
+        let num = |c: &Component| -> ParseResult<f32> {
+            match c.value {
+                Value::Number(x) if x >= 0. => Ok(x),
+                _ => Err(c.err()),
+            }
+        };
+        match parts.as_slice() {
+            [h, s, l] => Ok(ColorFormat::Hsl(num(h)?, num(s)?, num(l)?).normalize()),
+            [h, s, l, a] => Ok(ColorFormat::Hsla(num(h)?, num(s)?, num(l)?, num(a)?).normalize()),
+            _ => self.err(
+                offset,
+                ParseColorErrorKind::WrongArgCount {
+                    expected: 3,
+                    found: parts.len(),
+                },
+            ),
+        }
+    }
+
+    fn function(&mut self, offset: usize, name: &str) -> ParseResult<ColorFormat> {
+        let name = name.to_ascii_lowercase();
+        if name == "color" {
+            return self.color_function();
+        }
+        let args = self.args()?;
+        let alpha = args.alpha()?;
+        match name.as_str() {
+            "rgb" | "rgba" => {
+                let [r, g, b] = args.expect3()?;
+                let rgb = [r.number(255.)?, g.number(255.)?, b.number(255.)?];
+                Ok(ColorFormat::from_srgb(rgb.map(|c| c / 255.), alpha))
+            }
+            "hsl" | "hsla" => {
+                let [h, s, l] = args.expect3()?;
+                let (h, s, l) = (h.hue()?, s.percent()?, l.percent()?);
+                Ok(ColorFormat::Hsla(h, s, l, alpha * 100.)
+                    .normalize()
+                    .opaque_if_possible())
+            }
+            "hwb" => {
+                let [h, w, b] = args.expect3()?;
+                let hwb = [h.hue()?, w.percent()? / 100., b.percent()? / 100.];
+                Ok(ColorFormat::from_srgb(hwb_to_srgb(hwb), alpha))
+            }
+            "lab" => {
+                let [l, a, b] = args.expect3()?;
+                let (l, a, b) = (l.number(100.)?, a.number(125.)?, b.number(125.)?);
+                Ok(ColorFormat::Lab(l, a, b, alpha).normalize())
+            }
+            "lch" => {
+                let [l, c, h] = args.expect3()?;
+                let (l, c, h) = (l.number(100.)?, c.number(150.)?, h.hue()?);
+                Ok(ColorFormat::Lch(l, c, h, alpha).normalize())
+            }
+            "oklab" => {
+                let [l, a, b] = args.expect3()?;
+                let (l, a, b) = (l.number(1.)?, a.number(0.4)?, b.number(0.4)?);
+                Ok(ColorFormat::Oklab(l, a, b, alpha).normalize())
+            }
+            "oklch" => {
+                let [l, c, h] = args.expect3()?;
+                let (l, c, h) = (l.number(1.)?, c.number(0.4)?, h.hue()?);
+                Ok(ColorFormat::Oklch(l, c, h, alpha).normalize())
+            }
+            // not part of CSS, but written by ColorFormat::to_string for HCT colors
+            "hct" => {
+                let [h, c, t] = args.expect3()?;
+                let (h, c, t) = (h.hue()?, c.number(150.)?, t.number(100.)?);
+                Ok(ColorFormat::Hct(h, c, t, alpha).normalize())
+            }
+            _ => self.err(offset, ParseColorErrorKind::UnknownFunction(name)),
+        }
+    }
+
+    // color(<space> c1 c2 c3 [/ alpha])
+    fn color_function(&mut self) -> ParseResult<ColorFormat> {
+        self.skip_ws();
+        let offset = self.pos;
+        let space = self.ident().to_ascii_lowercase();
+        // the space is checked first, so that `color(1 0 0)` isn't reported as a component count
+        let to_linear: fn(Vec3) -> Vec3 = match space.as_str() {
+            "srgb" => |c| c.map(srgb_to_linear),
+            "srgb-linear" => |c| c,
+            "display-p3" => display_p3_to_linear_srgb,
+            "rec2020" => rec2020_to_linear_srgb,
+            "xyz" | "xyz-d65" => xyz_d65_to_linear_srgb,
+            "xyz-d50" => |c| xyz_d65_to_linear_srgb(xyz_d50_to_d65(c)),
+            "" if self.src.len() == offset => {
+                return self.err(offset, ParseColorErrorKind::UnexpectedEnd)
+            }
+            // a number where the space should be, as in `color(1 0 0)`
+            x if !x.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                return self.err(offset, ParseColorErrorKind::MissingColorSpace)
+            }
+            _ => return self.err(offset, ParseColorErrorKind::UnknownColorSpace(space)),
+        };
+        let args = self.args()?;
+        let alpha = args.alpha()?;
+        let [c0, c1, c2] = args.expect3()?;
+        let c = [c0.number(1.)?, c1.number(1.)?, c2.number(1.)?];
+        let linear = to_linear(c);
+        if in_srgb_gamut(linear_srgb_to_srgb(linear)) {
+            return Ok(ColorFormat::from_linear_srgb(linear, alpha));
+        }
+        // wide gamut colors would be clipped by RGB, OKLCH keeps them
+        let [l, c, h] = lab_to_lch(linear_srgb_to_oklab(linear));
+        Ok(ColorFormat::Oklch(l, c, h, alpha))
+    }
+}
+
+/// Parses any CSS Color Level 4 color, plus the "h,s,l" shorthand used by `ColorFormat`.
+pub fn parse_css_color(s: &str) -> ParseResult<ColorFormat> {
+    Parser::new(s).color()
+}
+
+pub fn named_color(name: &str) -> Option<ColorFormat> {
+    let name = name.to_ascii_lowercase();
+    if name == "transparent" {
+        return Some(ColorFormat::Rgba(0, 0, 0, 0));
+    }
+    let i = NAMED_COLORS
+        .binary_search_by(|(x, _)| x.cmp(&name.as_str()))
+        .ok()?;
+    let hex = NAMED_COLORS[i].1;
+    Some(ColorFormat::Rgb(
+        (hex >> 16) as u8,
+        (hex >> 8) as u8,
+        hex as u8,
+    ))
+}
+
+// sorted by name for binary search
+pub static NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Gamut;
+
+    fn parse(s: &str) -> ColorFormat {
+        parse_css_color(s).unwrap()
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse("#f0a"), ColorFormat::Rgb(255, 0, 170));
+        assert_eq!(parse("#f0a8"), ColorFormat::Rgba(255, 0, 170, 136));
+        assert_eq!(parse("#FF00AA"), ColorFormat::Rgb(255, 0, 170));
+        assert_eq!(parse("  #ff00aa80 "), ColorFormat::Rgba(255, 0, 170, 128));
+    }
+
+    #[test]
+    fn functions() {
+        let rose = ColorFormat::Rgb(244, 63, 94);
+        let red = ColorFormat::Rgb(255, 0, 0);
+        assert_eq!(parse("rgb(244, 63, 94)"), rose);
+        assert_eq!(parse("rgb(244 63 94)"), rose);
+        assert_eq!(parse("rgba(244 63 94 / 50%)"), rose.with_alpha(0.5));
+        assert_eq!(parse("rgb(100% 0% 0%)"), red);
+        assert_eq!(
+            parse("hsl(120deg 50% 40%)"),
+            ColorFormat::Hsl(120., 50., 40.)
+        );
+        assert_eq!(
+            parse("hsl(0.5turn, 50%, 40%)"),
+            ColorFormat::Hsl(180., 50., 40.)
+        );
+        assert_eq!(parse("hwb(0 0% 0%)"), red);
+        assert_eq!(parse("hwb(0 50% 50%)"), ColorFormat::Rgb(128, 128, 128));
+        assert_eq!(parse("lab(54.29 80.8 69.89)").to_rgb(), red);
+        assert_eq!(parse("lch(54.29% 106.84 40.85)").to_rgb(), red);
+        assert_eq!(parse("oklab(0.628 0.2249 0.1258)").to_rgb(), red);
+        assert_eq!(parse("oklch(62.8% 0.2577 29.23)").to_rgb(), red);
+        assert_eq!(
+            parse("oklch(1 100% 0 / 0.5)"),
+            ColorFormat::Oklch(1., 0.4, 0., 0.5)
+        );
+        assert_eq!(parse("color(srgb 1 0 0)"), red);
+        assert_eq!(parse("color(display-p3 0.9175 0.2003 0.1386)"), red);
+        let white = ColorFormat::Rgba(255, 255, 255, 0);
+        assert_eq!(parse("color(rec2020 1 1 1 / 0)"), white);
+        let p3_red = parse("color(display-p3 1 0 0)");
+        assert!(matches!(p3_red, ColorFormat::Oklch(..)));
+        assert!(!Gamut::Srgb.contains(p3_red));
+        assert!(Gamut::DisplayP3.contains(p3_red));
+        assert_eq!(
+            parse("oklch(none none none)").to_rgb(),
+            ColorFormat::Rgb(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("RebeccaPurple"), ColorFormat::Rgb(0x66, 0x33, 0x99));
+        assert_eq!(parse("transparent"), ColorFormat::Rgba(0, 0, 0, 0));
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| parse_css_color(s).unwrap_err();
+        assert_eq!(err("").kind, ParseColorErrorKind::Empty);
+        assert_eq!(err("#ff").kind, ParseColorErrorKind::InvalidHex);
+        assert_eq!(err("#ff00zz").offset, 1);
+        assert_eq!(
+            err("1,2").kind,
+            ParseColorErrorKind::WrongArgCount {
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            err("reddish").kind,
+            ParseColorErrorKind::UnknownName("reddish".into())
+        );
+        assert_eq!(err("rgb(1 2 3").kind, ParseColorErrorKind::UnexpectedEnd);
+        assert_eq!(err("rgb(1 2 3) x").offset, 11);
+        assert_eq!(err("hsl(10px 2% 3%)").offset, 6);
+        assert_eq!(err("color(a98-rgb 1 0 0)").offset, 6);
+        let missing = err("color(1 0 0)");
+        assert_eq!(missing.offset, 6);
+        assert_eq!(missing.kind, ParseColorErrorKind::MissingColorSpace);
+    }
+}
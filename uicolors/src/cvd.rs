@@ -0,0 +1,115 @@
+// Color vision deficiency simulation, using the Machado, Oliveira and Fernandes (2009) matrices
+// at full severity for dichromacy, and luminance only for achromatopsia.
+
+use crate::{colorspace::*, ColorFormat};
+use gpui::{Hsla, Rgba};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorVision {
+    Normal,
+    Protanopia,    // no red cones
+    Deuteranopia,  // no green cones
+    Tritanopia,    // no blue cones
+    Achromatopsia, // no color vision
+}
+
+#[rustfmt::skip]
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+
+#[rustfmt::skip]
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+
+#[rustfmt::skip]
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+impl ColorVision {
+    pub const ALL: [ColorVision; 5] = [
+        ColorVision::Normal,
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+        ColorVision::Achromatopsia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorVision::Normal => "Normal",
+            ColorVision::Protanopia => "Protanopia",
+            ColorVision::Deuteranopia => "Deuteranopia",
+            ColorVision::Tritanopia => "Tritanopia",
+            ColorVision::Achromatopsia => "Achromatopsia",
+        }
+    }
+
+    /// Simulates how gamma-encoded sRGB channels (0..1) are perceived.
+    pub fn simulate_srgb(self, rgb: Vec3) -> Vec3 {
+        let m = match self {
+            ColorVision::Normal => return rgb,
+            ColorVision::Protanopia => &PROTANOPIA,
+            ColorVision::Deuteranopia => &DEUTERANOPIA,
+            ColorVision::Tritanopia => &TRITANOPIA,
+            ColorVision::Achromatopsia => {
+                let [r, g, b] = srgb_to_linear_srgb(rgb);
+                let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                return [linear_to_srgb(y); 3];
+            }
+        };
+        let [r, g, b] = srgb_to_linear_srgb(rgb);
+        let rgb = [0, 1, 2].map(|i| (m[i][0] * r + m[i][1] * g + m[i][2] * b).clamp(0., 1.));
+        linear_srgb_to_srgb(rgb)
+    }
+
+    pub fn simulate(self, color: ColorFormat) -> ColorFormat {
+        if self == ColorVision::Normal {
+            return color;
+        }
+        ColorFormat::from_srgb(self.simulate_srgb(color.to_srgb()), color.alpha())
+    }
+
+    pub fn simulate_hsla(self, color: Hsla) -> Hsla {
+        if self == ColorVision::Normal {
+            return color;
+        }
+        let Rgba { r, g, b, a } = color.into();
+        let [r, g, b] = self.simulate_srgb([r, g, b]);
+        Rgba { r, g, b, a }.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn simulate() {
+        let (red, green) = (ColorFormat::Rgb(255, 0, 0), ColorFormat::Rgb(0, 128, 0));
+        let white = ColorFormat::Rgb(255, 255, 255);
+        for vision in ColorVision::ALL {
+            // neutral colors look the same to everyone
+            let (r, g, b) = vision.simulate(white).split_rgb();
+            assert!(r >= 253 && g >= 253 && b >= 253, "{:?}", vision);
+        }
+        assert_eq!(ColorVision::Normal.simulate(red), red);
+        let (r, g, b) = ColorVision::Achromatopsia.simulate(red).split_rgb();
+        assert!(r == g && g == b);
+        // red and green become hard to tell apart for red-green deficiencies
+        for vision in [ColorVision::Protanopia, ColorVision::Deuteranopia] {
+            let (r0, g0, _) = vision.simulate(red).split_rgb();
+            let (r1, g1, _) = vision.simulate(green).split_rgb();
+            assert!((r0 as i32 - g0 as i32).abs() < 40, "{:?}", vision);
+            assert!((r1 as i32 - g1 as i32).abs() < 40, "{:?}", vision);
+        }
+    }
+}
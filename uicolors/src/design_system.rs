@@ -0,0 +1,319 @@
+use crate::{colorspace::*, write_atomic, ColorFormat, ColorPalette, CubicBezier, PaletteMode};
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// What a palette is used for in a design system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaletteRole {
+    Primary,
+    Secondary,
+    Neutral,
+    Success,
+    Warning,
+    Error,
+    Info,
+}
+
+impl PaletteRole {
+    pub const ALL: [PaletteRole; 7] = [
+        PaletteRole::Primary,
+        PaletteRole::Secondary,
+        PaletteRole::Neutral,
+        PaletteRole::Success,
+        PaletteRole::Warning,
+        PaletteRole::Error,
+        PaletteRole::Info,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteRole::Primary => "Primary",
+            PaletteRole::Secondary => "Secondary",
+            PaletteRole::Neutral => "Neutral",
+            PaletteRole::Success => "Success",
+            PaletteRole::Warning => "Warning",
+            PaletteRole::Error => "Error",
+            PaletteRole::Info => "Info",
+        }
+    }
+
+    /// Starting color of a new palette with this role. The secondary color is the complement of
+    /// the primary one and neutrals are tinted with its hue, semantic colors have fixed hues.
+    pub fn suggested_color(self, primary: ColorFormat) -> ColorFormat {
+        let (l, c, h) = primary.split_oklch();
+        let lch = match self {
+            PaletteRole::Primary => return primary,
+            PaletteRole::Secondary => [l, c, (h + 180.).rem_euclid(360.)],
+            PaletteRole::Neutral => [0.55, c.min(0.02), h],
+            PaletteRole::Success => [0.63, 0.17, 150.],
+            PaletteRole::Warning => [0.77, 0.17, 70.],
+            PaletteRole::Error => [0.63, 0.22, 25.],
+            PaletteRole::Info => [0.62, 0.19, 250.],
+        };
+        ColorFormat::from_srgb(oklch_to_srgb_gamut_mapped(lch), 1.)
+    }
+}
+
+/// A named palette: its base color and the settings it is generated with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaletteSpec {
+    pub name: String,
+    pub color: ColorFormat,
+    pub mode: PaletteMode,
+    pub hue_shift: f32,
+    pub lightness_range: (f32, f32),
+    pub lightness_curve: CubicBezier,
+    pub chroma_curve: CubicBezier,
+}
+
+impl PaletteSpec {
+    pub fn new(name: impl Into<String>, color: ColorFormat, palette: &ColorPalette) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            mode: palette.mode,
+            hue_shift: palette.hue_shift,
+            lightness_range: palette.lightness_range,
+            lightness_curve: palette.lightness_curve,
+            chroma_curve: palette.chroma_curve,
+        }
+    }
+
+    pub fn palette(&self) -> ColorPalette {
+        let (lightest, darkest) = self.lightness_range;
+        ColorPalette::from(self.color.canonicalize())
+            .hue_shift(self.hue_shift)
+            .mode(self.mode)
+            .lightness_range(lightest, darkest)
+            .lightness_curve(self.lightness_curve)
+            .chroma_curve(self.chroma_curve)
+    }
+}
+
+/// The set of palettes of a project, saved as a project file. The main color and palette being
+/// edited are the ones of the active palette.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DesignSystem {
+    #[serde(default)]
+    pub version: u32,
+    pub palettes: Vec<PaletteSpec>,
+    // role -> name of the palette that has it
+    #[serde(default)]
+    pub roles: IndexMap<PaletteRole, String>,
+    #[serde(skip)]
+    active: usize,
+}
+
+impl DesignSystem {
+    /// Version of `.uicolors` project files, versioned like the library (see
+    /// `ColorLibrary::VERSION`) but independently of it: projects are shared between people
+    /// whose builds may differ.
+    pub const VERSION: u32 = 1;
+
+    pub const EXTENSION: &'static str = "uicolors";
+
+    /// A design system with a single primary palette.
+    pub fn new(color: ColorFormat, palette: &ColorPalette) -> Self {
+        let name = PaletteRole::Primary.name().to_lowercase();
+        Self {
+            version: Self::VERSION,
+            palettes: vec![PaletteSpec::new(name.clone(), color, palette)],
+            roles: IndexMap::from([(PaletteRole::Primary, name)]),
+            active: 0,
+        }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_palette(&self) -> &PaletteSpec {
+        &self.palettes[self.active]
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.palettes.len() {
+            self.active = index;
+        }
+    }
+
+    /// Keeps the active palette in sync with the color being edited.
+    pub fn update_active(&mut self, color: ColorFormat, palette: &ColorPalette) {
+        let spec = &mut self.palettes[self.active];
+        *spec = PaletteSpec::new(std::mem::take(&mut spec.name), color, palette);
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.palettes.iter().position(|x| x.name == name)
+    }
+
+    pub fn role_of(&self, index: usize) -> Option<PaletteRole> {
+        let name = &self.palettes.get(index)?.name;
+        self.roles
+            .iter()
+            .find(|(_, x)| *x == name)
+            .map(|(role, _)| *role)
+    }
+
+    pub fn palette_with_role(&self, role: PaletteRole) -> Option<&PaletteSpec> {
+        self.position(self.roles.get(&role)?)
+            .map(|i| &self.palettes[i])
+    }
+
+    /// Adds a palette for the role, generated like the active palette from the suggested color of
+    /// the role. Returns the index of the palette with the role.
+    pub fn add_role(&mut self, role: PaletteRole) -> usize {
+        if let Some(index) = self.roles.get(&role).and_then(|name| self.position(name)) {
+            return index;
+        }
+        let primary = self
+            .palette_with_role(PaletteRole::Primary)
+            .unwrap_or(self.active_palette());
+        let color = role.suggested_color(primary.color);
+        let name = self.unique_name(&role.name().to_lowercase());
+        let spec = PaletteSpec {
+            name: name.clone(),
+            color,
+            ..self.active_palette().clone()
+        };
+        self.palettes.push(spec);
+        self.roles.insert(role, name);
+        self.palettes.len() - 1
+    }
+
+    fn unique_name(&self, name: &str) -> String {
+        let mut unique = name.to_owned();
+        let mut n = 2;
+        while self.position(&unique).is_some() {
+            unique = format!("{}-{}", name, n);
+            n += 1;
+        }
+        unique
+    }
+
+    /// Removes a palette and its role. The last palette can't be removed.
+    pub fn remove(&mut self, index: usize) -> Option<PaletteSpec> {
+        if self.palettes.len() <= 1 || index >= self.palettes.len() {
+            return None;
+        }
+        let spec = self.palettes.remove(index);
+        self.roles.retain(|_, name| *name != spec.name);
+        if self.active > index || self.active == self.palettes.len() {
+            self.active -= 1;
+        }
+        Some(spec)
+    }
+
+    /// Every palette by name, in order, for `export_palettes`.
+    pub fn export_palettes(&self) -> Vec<(String, ColorPalette)> {
+        self.palettes
+            .iter()
+            .map(|x| (x.name.clone(), x.palette()))
+            .collect()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let design_system: Self = serde_json::from_str(json)?;
+        design_system.migrate()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn migrate(mut self) -> Result<Self> {
+        if self.version > Self::VERSION {
+            return Err(anyhow!(
+                "project version {} is newer than supported version {}",
+                self.version,
+                Self::VERSION
+            ));
+        }
+        if self.palettes.is_empty() {
+            return Err(anyhow!("project has no palettes"));
+        }
+        // roles of palettes that were removed by hand
+        let names: Vec<String> = self.palettes.iter().map(|x| x.name.clone()).collect();
+        self.roles.retain(|_, name| names.contains(name));
+        self.version = Self::VERSION;
+        Ok(self)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = self.to_json()?;
+        write_atomic(path, json.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{export_palettes, ExportFormat};
+
+    fn design_system() -> DesignSystem {
+        let color = ColorFormat::Rgb(0x3b, 0x82, 0xf6);
+        let palette = ColorPalette::from(color.canonicalize()).mode(PaletteMode::Oklch);
+        DesignSystem::new(color, &palette)
+    }
+
+    #[test]
+    fn roles() {
+        let mut ds = design_system();
+        let neutral = ds.add_role(PaletteRole::Neutral);
+        assert_eq!(ds.add_role(PaletteRole::Neutral), neutral);
+        assert_eq!(ds.palettes[neutral].name, "neutral");
+        assert_eq!(ds.palettes[neutral].mode, PaletteMode::Oklch);
+        assert_eq!(ds.role_of(neutral), Some(PaletteRole::Neutral));
+        let (_, c, _) = ds.palettes[neutral].color.split_oklch();
+        assert!(c <= 0.03, "{}", c);
+
+        let error = ds.add_role(PaletteRole::Error);
+        ds.set_active(error);
+        assert_eq!(ds.remove(neutral).unwrap().name, "neutral");
+        assert_eq!(ds.role_of(neutral), Some(PaletteRole::Error));
+        assert_eq!(ds.active(), neutral);
+        assert!(ds.palette_with_role(PaletteRole::Neutral).is_none());
+
+        ds.remove(neutral);
+        assert_eq!(ds.remove(0), None);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut ds = design_system();
+        ds.add_role(PaletteRole::Secondary);
+        ds.add_role(PaletteRole::Success);
+        let json = ds.to_json().unwrap();
+        let loaded = DesignSystem::from_json(&json).unwrap();
+        assert_eq!(loaded.palettes, ds.palettes);
+        assert_eq!(loaded.roles, ds.roles);
+        assert!(json.contains("\"secondary\": \"secondary\""), "{}", json);
+
+        let newer = json.replace("\"version\": 1", "\"version\": 99");
+        assert!(DesignSystem::from_json(&newer).is_err());
+        let dangling = json.replace("\"success\": \"success\"", "\"success\": \"green\"");
+        let loaded = DesignSystem::from_json(&dangling).unwrap();
+        assert!(loaded.palette_with_role(PaletteRole::Success).is_none());
+    }
+
+    #[test]
+    fn export_all() {
+        let mut ds = design_system();
+        ds.add_role(PaletteRole::Warning);
+        let json = export_palettes(&ds.export_palettes(), ExportFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let primary_500 = ColorFormat::from(ds.palettes[0].palette().at_darkness(500));
+        assert_eq!(value["primary"]["500"], primary_500.to_rgb().to_string());
+        assert!(value["warning"]["50"].is_string());
+    }
+}
@@ -0,0 +1,199 @@
+// How different two colors look: delta E in CIE Lab (1976 and 2000) and in OKLab, and the
+// named color nearest to a color.
+
+use crate::{colorspace::*, ColorFormat, ColorLibrary, TAILWIND_COLORS};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeltaE {
+    /// Euclidean distance in CIE Lab. Simple, but overstates differences between vivid colors.
+    Cie76,
+    /// CIEDE2000, which corrects CIE76 for how we see chroma and hue.
+    Ciede2000,
+    /// Euclidean distance in OKLab, in OKLab's 0..1 lightness units.
+    Oklab,
+}
+
+impl DeltaE {
+    pub const ALL: [DeltaE; 3] = [DeltaE::Cie76, DeltaE::Ciede2000, DeltaE::Oklab];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DeltaE::Cie76 => "ΔE76",
+            DeltaE::Ciede2000 => "ΔE2000",
+            DeltaE::Oklab => "ΔEok",
+        }
+    }
+
+    /// Roughly the smallest difference most people notice side by side.
+    pub fn just_noticeable(self) -> f32 {
+        match self {
+            DeltaE::Cie76 => 2.3,
+            DeltaE::Ciede2000 => 1.,
+            DeltaE::Oklab => 0.02,
+        }
+    }
+
+    /// Difference between two colors, ignoring alpha. Symmetric, and 0 for the same color.
+    pub fn between(self, a: ColorFormat, b: ColorFormat) -> f32 {
+        let distance = |a: Vec3, b: Vec3| {
+            let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+            (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+        };
+        let lab = |c: ColorFormat| linear_srgb_to_lab(c.to_linear_srgb());
+        match self {
+            DeltaE::Cie76 => distance(lab(a), lab(b)),
+            DeltaE::Ciede2000 => ciede2000(lab(a), lab(b)),
+            DeltaE::Oklab => {
+                let oklab = |c: ColorFormat| linear_srgb_to_oklab(c.to_linear_srgb());
+                distance(oklab(a), oklab(b))
+            }
+        }
+    }
+}
+
+// CIEDE2000 with the reference weights kL = kC = kH = 1, computed in f64 as the formula is
+// sensitive around hue differences of 180°
+fn ciede2000(lab1: Vec3, lab2: Vec3) -> f32 {
+    let [l1, a1, b1] = lab1.map(|x| x as f64);
+    let [l2, a2, b2] = lab2.map(|x| x as f64);
+    let pow7 = |x: f64| x.powi(7);
+    let hue = |b: f64, a: f64| {
+        if a == 0. && b == 0. {
+            0.
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.)
+        }
+    };
+
+    let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.;
+    let g = 0.5 * (1. - (pow7(c_mean) / (pow7(c_mean) + pow7(25.))).sqrt());
+    let (a1, a2) = ((1. + g) * a1, (1. + g) * a2);
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0. {
+        0.
+    } else {
+        match h2 - h1 {
+            d if d > 180. => d - 360.,
+            d if d < -180. => d + 360.,
+            d => d,
+        }
+    };
+    let dh = 2. * (c1 * c2).sqrt() * (dh / 2.).to_radians().sin();
+
+    let l_mean = (l1 + l2) / 2.;
+    let c_mean = (c1 + c2) / 2.;
+    let h_mean = if c1 * c2 == 0. {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180. {
+        (h1 + h2) / 2.
+    } else if h1 + h2 < 360. {
+        (h1 + h2 + 360.) / 2.
+    } else {
+        (h1 + h2 - 360.) / 2.
+    };
+    let cos = |deg: f64| deg.to_radians().cos();
+    let t = 1. - 0.17 * cos(h_mean - 30.) + 0.24 * cos(2. * h_mean) + 0.32 * cos(3. * h_mean + 6.)
+        - 0.20 * cos(4. * h_mean - 63.);
+    let d_theta = 30. * (-((h_mean - 275.) / 25.).powi(2)).exp();
+    let rc = 2. * (pow7(c_mean) / (pow7(c_mean) + pow7(25.))).sqrt();
+    let sl = 1. + 0.015 * (l_mean - 50.).powi(2) / (20. + (l_mean - 50.).powi(2)).sqrt();
+    let sc = 1. + 0.045 * c_mean;
+    let sh = 1. + 0.015 * c_mean * t;
+    let rt = -(2. * d_theta).to_radians().sin() * rc;
+
+    let (dl, dc, dh) = (dl / sl, dc / sc, dh / sh);
+    (dl * dl + dc * dc + dh * dh + rt * dc * dh).sqrt() as f32
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedColor {
+    pub name: String,
+    pub color: ColorFormat,
+    pub library_index: Option<usize>, // None for Tailwind colors
+    pub distance: f32,
+}
+
+/// The named color closest to `color`, among the named colors of the library and the Tailwind
+/// palette. The library wins ties, so a saved color is found by its own name.
+pub fn nearest_named_color(
+    color: ColorFormat,
+    library: Option<&ColorLibrary>,
+    metric: DeltaE,
+) -> Option<NamedColor> {
+    let library_colors = library.into_iter().flat_map(|library| {
+        let items = library.items.iter().enumerate();
+        items.filter_map(|(i, item)| Some((item.name.as_ref()?, item.color, Some(i))))
+    });
+    let tailwind_colors = (TAILWIND_COLORS.iter()).map(|(name, color)| (name, *color, None));
+    library_colors
+        .chain(tailwind_colors)
+        .map(|(name, candidate, library_index)| NamedColor {
+            name: name.clone(),
+            color: candidate,
+            library_index,
+            distance: metric.between(color, candidate),
+        })
+        .reduce(|best, x| if x.distance < best.distance { x } else { best })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ciede2000_reference_pairs() {
+        // from Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula" (2005)
+        let pairs = [
+            ([50., 2.6772, -79.7751], [50., 0., -82.7485], 2.0425),
+            ([50., 0., 0.], [50., -1., 2.], 2.3669),
+            ([50., 2.49, -0.001], [50., -2.49, 0.0011], 7.2195),
+            ([50., 2.5, 0.], [73., 25., -18.], 27.1492),
+            (
+                [60.2574, -34.0099, 36.2677],
+                [60.4626, -34.1751, 39.4387],
+                1.2644,
+            ),
+            ([2.0776, 0.0795, -1.135], [0.9033, -0.0636, -0.5514], 0.9082),
+        ];
+        for (lab1, lab2, expected) in pairs {
+            let d = ciede2000(lab1, lab2);
+            assert!((d - expected).abs() < 1e-4, "{:?} {:?}: {}", lab1, lab2, d);
+            assert!((ciede2000(lab2, lab1) - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn between() {
+        let red = ColorFormat::Rgb(255, 0, 0);
+        for metric in DeltaE::ALL {
+            assert!(metric.between(red, red.to_oklch()) < 1e-3, "{:?}", metric);
+        }
+        let (black, white) = (ColorFormat::Rgb(0, 0, 0), ColorFormat::Rgb(255, 255, 255));
+        assert!((DeltaE::Cie76.between(black, white) - 100.).abs() < 0.01);
+        assert!((DeltaE::Ciede2000.between(black, white) - 100.).abs() < 0.01);
+        assert!((DeltaE::Oklab.between(black, white) - 1.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn nearest() {
+        let library = ColorLibrary::default();
+        let rose = ColorFormat::Rgb(0xf4, 0x3f, 0x5e);
+        let found = nearest_named_color(rose, Some(&library), DeltaE::Ciede2000).unwrap();
+        assert_eq!(
+            (found.name.as_str(), found.library_index),
+            ("Rose", Some(0))
+        );
+        assert!(found.distance < 1e-3);
+
+        let found = nearest_named_color(rose, None, DeltaE::Ciede2000).unwrap();
+        assert_eq!(found.name, "rose-500");
+        let almost_sky = ColorFormat::Rgb(0x0f, 0xa4, 0xe8);
+        let found = nearest_named_color(almost_sky, None, DeltaE::Oklab).unwrap();
+        assert_eq!(found.name, "sky-500");
+        assert!(found.distance > 0. && found.distance < DeltaE::Oklab.just_noticeable());
+    }
+}
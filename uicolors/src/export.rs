@@ -0,0 +1,428 @@
+// Palette exporters: code snippets for the tools and platforms a palette ends up in.
+
+use crate::{tailwind_class, ColorFormat, ColorPalette, SCALES};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Tailwind,
+    Css,
+    Scss,
+    DesignTokens,
+    SwiftUi,
+    UiKit,
+    Compose,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 8] = [
+        ExportFormat::Json,
+        ExportFormat::Tailwind,
+        ExportFormat::Css,
+        ExportFormat::Scss,
+        ExportFormat::DesignTokens,
+        ExportFormat::SwiftUi,
+        ExportFormat::UiKit,
+        ExportFormat::Compose,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Tailwind => "Tailwind",
+            ExportFormat::Css => "CSS",
+            ExportFormat::Scss => "SCSS",
+            ExportFormat::DesignTokens => "Design Tokens",
+            ExportFormat::SwiftUi => "SwiftUI",
+            ExportFormat::UiKit => "UIKit",
+            ExportFormat::Compose => "Compose",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Tailwind => "js",
+            ExportFormat::Css => "css",
+            ExportFormat::Scss => "scss",
+            ExportFormat::DesignTokens => "tokens.json",
+            ExportFormat::SwiftUi | ExportFormat::UiKit => "swift",
+            ExportFormat::Compose => "kt",
+        }
+    }
+
+    /// Parses the names used on the command line, e.g. `tailwind` or `tokens`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let format = match name.to_lowercase().as_str() {
+            "json" => ExportFormat::Json,
+            "tailwind" => ExportFormat::Tailwind,
+            "css" => ExportFormat::Css,
+            "scss" => ExportFormat::Scss,
+            "tokens" | "design-tokens" => ExportFormat::DesignTokens,
+            "swiftui" | "swift" => ExportFormat::SwiftUi,
+            "uikit" => ExportFormat::UiKit,
+            "compose" | "kotlin" => ExportFormat::Compose,
+            _ => return None,
+        };
+        Some(format)
+    }
+}
+
+/// How a single color is written when copied, e.g. from a palette swatch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyFormat {
+    #[default]
+    Hex,
+    Rgb,
+    Hsl,
+    Oklch,
+    Tailwind,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 5] = [
+        CopyFormat::Hex,
+        CopyFormat::Rgb,
+        CopyFormat::Hsl,
+        CopyFormat::Oklch,
+        CopyFormat::Tailwind,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CopyFormat::Hex => "Hex",
+            CopyFormat::Rgb => "rgb()",
+            CopyFormat::Hsl => "hsl()",
+            CopyFormat::Oklch => "oklch()",
+            CopyFormat::Tailwind => "Tailwind",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let format = match name.to_lowercase().as_str() {
+            "hex" => CopyFormat::Hex,
+            "rgb" => CopyFormat::Rgb,
+            "hsl" => CopyFormat::Hsl,
+            "oklch" => CopyFormat::Oklch,
+            "tailwind" => CopyFormat::Tailwind,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// The color as CSS, in modern space separated syntax, or as a Tailwind class.
+    pub fn format(self, color: ColorFormat) -> String {
+        match self {
+            CopyFormat::Hex => color.to_rgb().to_string(),
+            CopyFormat::Rgb => {
+                let (r, g, b) = color.to_rgb().split_rgb();
+                match color.alpha() {
+                    a if a < 1. => format!("rgb({} {} {} / {:.3})", r, g, b, a),
+                    _ => format!("rgb({} {} {})", r, g, b),
+                }
+            }
+            CopyFormat::Hsl => {
+                let (h, s, l) = color.to_hsl().split_hsl();
+                let [h, s, l] = [h, s, l].map(f32::round);
+                match color.alpha() {
+                    a if a < 1. => format!("hsl({} {}% {}% / {:.3})", h, s, l, a),
+                    _ => format!("hsl({} {}% {}%)", h, s, l),
+                }
+            }
+            CopyFormat::Oklch => color.to_oklch().to_string(),
+            CopyFormat::Tailwind => tailwind_class(color),
+        }
+    }
+}
+
+/// Exports one palette. Names may be in any case, e.g. "Primary" or "brand accent".
+pub fn export_palette(name: &str, palette: ColorPalette, format: ExportFormat) -> String {
+    export_palettes(&[(name.to_owned(), palette)], format)
+}
+
+/// Exports several palettes into one snippet. A single palette exported as JSON is a flat
+/// `{ "50": "#..." }` map, several palettes are nested by name.
+pub fn export_palettes(palettes: &[(String, ColorPalette)], format: ExportFormat) -> String {
+    let shades = |palette: &ColorPalette| -> Vec<(usize, ColorFormat)> {
+        SCALES
+            .iter()
+            .map(|sc| (*sc, ColorFormat::from(palette.at_darkness(*sc)).to_rgb()))
+            .collect()
+    };
+
+    let mut out = String::new();
+    match format {
+        ExportFormat::Json => {
+            let map = |palette: &ColorPalette| -> IndexMap<usize, String> {
+                shades(palette)
+                    .into_iter()
+                    .map(|(sc, c)| (sc, c.to_string()))
+                    .collect()
+            };
+            let json = match palettes {
+                [(_, palette)] => serde_json::to_string_pretty(&map(palette)),
+                _ => {
+                    let all: IndexMap<String, IndexMap<usize, String>> = palettes
+                        .iter()
+                        .map(|(name, palette)| (kebab_case(name), map(palette)))
+                        .collect();
+                    serde_json::to_string_pretty(&all)
+                }
+            };
+            out = json.unwrap_or_default();
+        }
+        ExportFormat::Tailwind => {
+            out.push_str("module.exports = {\n  theme: {\n    extend: {\n      colors: {\n");
+            for (name, palette) in palettes {
+                out.push_str(&format!("        '{}': {{\n", kebab_case(name)));
+                for (sc, c) in shades(palette) {
+                    out.push_str(&format!("          {}: '{}',\n", sc, c));
+                }
+                out.push_str("        },\n");
+            }
+            out.push_str("      },\n    },\n  },\n};\n");
+        }
+        ExportFormat::Css => {
+            out.push_str(":root {\n");
+            for (name, palette) in palettes {
+                for (sc, c) in shades(palette) {
+                    out.push_str(&format!("  --{}-{}: {};\n", kebab_case(name), sc, c));
+                }
+            }
+            out.push_str("}\n");
+        }
+        ExportFormat::Scss => {
+            for (i, (name, palette)) in palettes.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                }
+                out.push_str(&format!("${}: (\n", kebab_case(name)));
+                for (sc, c) in shades(palette) {
+                    out.push_str(&format!("  {}: {},\n", sc, c));
+                }
+                out.push_str(");\n");
+            }
+        }
+        ExportFormat::DesignTokens => {
+            // https://tr.designtokens.org/format/
+            let mut root = serde_json::Map::new();
+            for (name, palette) in palettes {
+                let mut group = serde_json::Map::new();
+                for (sc, c) in shades(palette) {
+                    group.insert(
+                        sc.to_string(),
+                        json!({ "$type": "color", "$value": c.to_string() }),
+                    );
+                }
+                root.insert(kebab_case(name), Value::Object(group));
+            }
+            out = serde_json::to_string_pretty(&Value::Object(root)).unwrap_or_default();
+        }
+        ExportFormat::SwiftUi => {
+            out.push_str("import SwiftUI\n\nextension Color {\n");
+            for (name, palette) in palettes {
+                for (sc, c) in shades(palette) {
+                    let [r, g, b] = c.to_srgb();
+                    let opacity = match c.alpha() {
+                        a if a < 1. => format!(", opacity: {:.3}", a),
+                        _ => "".to_owned(),
+                    };
+                    out.push_str(&format!(
+                        "    static let {}{} = Color(red: {:.3}, green: {:.3}, blue: {:.3}{})\n",
+                        camel_case(name),
+                        sc,
+                        r,
+                        g,
+                        b,
+                        opacity
+                    ));
+                }
+            }
+            out.push_str("}\n");
+        }
+        ExportFormat::UiKit => {
+            out.push_str("import UIKit\n\nextension UIColor {\n");
+            for (name, palette) in palettes {
+                for (sc, c) in shades(palette) {
+                    let [r, g, b] = c.to_srgb();
+                    out.push_str(&format!(
+                        "    static let {}{} = UIColor(red: {:.3}, green: {:.3}, blue: {:.3}, alpha: {:.3})\n",
+                        camel_case(name),
+                        sc,
+                        r,
+                        g,
+                        b,
+                        c.alpha()
+                    ));
+                }
+            }
+            out.push_str("}\n");
+        }
+        ExportFormat::Compose => {
+            out.push_str("import androidx.compose.ui.graphics.Color\n\n");
+            for (name, palette) in palettes {
+                for (sc, c) in shades(palette) {
+                    let (r, g, b) = c.split_rgb();
+                    let a = (c.alpha() * 255.).round() as u8;
+                    out.push_str(&format!(
+                        "val {}{} = Color(0x{:02X}{:02X}{:02X}{:02X})\n",
+                        pascal_case(name),
+                        sc,
+                        a,
+                        r,
+                        g,
+                        b
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+// "Brand accent", "brandAccent" and "brand_accent" are all the words ["brand", "accent"]
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for ch in name.chars() {
+        if !ch.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if ch.is_uppercase() && prev_lower && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = ch.is_lowercase() || ch.is_numeric();
+        word.extend(ch.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    if words.is_empty() {
+        words.push("color".to_owned());
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub fn kebab_case(name: &str) -> String {
+    words(name).join("-")
+}
+
+// Swift and Kotlin identifiers can't start with a digit, so "500" becomes "color500"
+fn identifier_words(name: &str) -> Vec<String> {
+    let mut words = words(name);
+    if !words[0].starts_with(char::is_alphabetic) {
+        words.insert(0, "color".to_owned());
+    }
+    words
+}
+
+pub fn camel_case(name: &str) -> String {
+    let words = identifier_words(name);
+    let mut s = words[0].clone();
+    for word in &words[1..] {
+        s.push_str(&capitalize(word));
+    }
+    s
+}
+
+pub fn pascal_case(name: &str) -> String {
+    identifier_words(name)
+        .iter()
+        .map(|w| capitalize(w))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(kebab_case("Brand Accent"), "brand-accent");
+        assert_eq!(kebab_case("brandAccent"), "brand-accent");
+        assert_eq!(camel_case("brand_accent"), "brandAccent");
+        assert_eq!(pascal_case("primary"), "Primary");
+        assert_eq!(kebab_case("  "), "color");
+        assert_eq!(camel_case("500"), "color500");
+        assert_eq!(camel_case("2024-brand"), "color2024Brand");
+        assert_eq!(pascal_case("2024 brand"), "Color2024Brand");
+        assert_eq!(kebab_case("500"), "500");
+    }
+
+    #[test]
+    fn copy_formats() {
+        let blue = ColorFormat::Rgb(0x3b, 0x82, 0xf6);
+        let copied = CopyFormat::ALL.map(|x| x.format(blue));
+        assert_eq!(copied[0], "#3b82f6");
+        assert_eq!(copied[1], "rgb(59 130 246)");
+        assert_eq!(copied[2], "hsl(217 91% 60%)");
+        assert!(copied[3].starts_with("oklch(0.623"));
+        assert_eq!(copied[4], "bg-blue-500");
+        let translucent = blue.with_alpha(0.5);
+        assert_eq!(
+            CopyFormat::Rgb.format(translucent),
+            "rgb(59 130 246 / 0.502)"
+        );
+        assert_eq!(CopyFormat::from_name("OKLCH"), Some(CopyFormat::Oklch));
+    }
+
+    #[test]
+    fn formats() {
+        let palette = ColorPalette::from(ColorFormat::from("#ef4444").canonicalize());
+        let c500 = ColorFormat::from(palette.at_darkness(500))
+            .to_rgb()
+            .to_string();
+
+        let json = export_palette("red", palette, ExportFormat::Json);
+        let map: IndexMap<String, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map.len(), SCALES.len());
+        assert_eq!(map["500"], c500);
+
+        let tw = export_palette("red", palette, ExportFormat::Tailwind);
+        assert!(tw.contains("colors: {\n        'red': {\n          50: '#"));
+        assert!(tw.contains(&format!("500: '{}',", c500)));
+
+        let css = export_palette("Red", palette, ExportFormat::Css);
+        assert!(css.contains(&format!("  --red-500: {};\n", c500)));
+
+        let scss = export_palette("red", palette, ExportFormat::Scss);
+        assert!(scss.starts_with("$red: (\n  50: #"));
+
+        let tokens = export_palette("red", palette, ExportFormat::DesignTokens);
+        let tokens: Value = serde_json::from_str(&tokens).unwrap();
+        assert_eq!(tokens["red"]["500"]["$type"], "color");
+        assert_eq!(tokens["red"]["500"]["$value"], c500.as_str());
+
+        let swift = export_palette("brand red", palette, ExportFormat::SwiftUi);
+        assert!(swift.contains("static let brandRed500 = Color(red: "));
+        let uikit = export_palette("red", palette, ExportFormat::UiKit);
+        assert!(uikit.contains("static let red500 = UIColor(red: "));
+
+        let kt = export_palette("red", palette, ExportFormat::Compose);
+        let hex = c500.trim_start_matches('#').to_uppercase();
+        assert!(kt.contains(&format!("val Red500 = Color(0xFF{})", hex)));
+
+        // several palettes are nested by name
+        let both = [("red".to_owned(), palette), ("blue".to_owned(), palette)];
+        let json = export_palettes(&both, ExportFormat::Json);
+        let map: IndexMap<String, IndexMap<String, String>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["red", "blue"]);
+    }
+}
@@ -0,0 +1,154 @@
+// Whether colors fit in the sRGB and Display P3 gamuts, and how to bring them in when they
+// don't. Colors edited in OKLab, OKLCH, Lab or LCH can go beyond what either can show.
+
+use crate::{colorspace::*, ColorFormat};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gamut {
+    Srgb,
+    DisplayP3,
+}
+
+impl Gamut {
+    pub const ALL: [Gamut; 2] = [Gamut::Srgb, Gamut::DisplayP3];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Gamut::Srgb => "sRGB",
+            Gamut::DisplayP3 => "Display P3",
+        }
+    }
+
+    // gamma-encoded channels in this gamut's RGB space, in 0..1 when inside it
+    fn encode_linear_srgb(self, rgb: Vec3) -> Vec3 {
+        match self {
+            Gamut::Srgb => linear_srgb_to_srgb(rgb),
+            Gamut::DisplayP3 => linear_srgb_to_display_p3(rgb),
+        }
+    }
+
+    fn decode_to_linear_srgb(self, rgb: Vec3) -> Vec3 {
+        match self {
+            Gamut::Srgb => srgb_to_linear_srgb(rgb),
+            Gamut::DisplayP3 => display_p3_to_linear_srgb(rgb),
+        }
+    }
+
+    pub fn contains(self, color: ColorFormat) -> bool {
+        in_srgb_gamut(self.encode_linear_srgb(color.to_linear_srgb()))
+    }
+
+    /// The color itself when it fits in the gamut, otherwise the mapped color: an RGB color for
+    /// sRGB, and an OKLCH color for Display P3, which RGB can't express.
+    pub fn map(self, color: ColorFormat, mapping: GamutMapping) -> ColorFormat {
+        if self.contains(color) {
+            return color;
+        }
+        let alpha = color.alpha();
+        let linear = match mapping {
+            GamutMapping::Clip => {
+                let rgb = self.encode_linear_srgb(color.to_linear_srgb());
+                self.decode_to_linear_srgb(rgb.map(|c| c.clamp(0., 1.)))
+            }
+            GamutMapping::ReduceChroma => {
+                let (l, c, h) = color.split_oklch();
+                let to_linear = |c: f32| oklab_to_linear_srgb(lch_to_lab([l, c, h]));
+                let fits = |c: f32| in_srgb_gamut(self.encode_linear_srgb(to_linear(c)));
+                let (mut lo, mut hi) = (0., c);
+                while hi - lo > 1e-4 {
+                    let mid = (lo + hi) / 2.;
+                    if fits(mid) {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                to_linear(lo)
+            }
+        };
+        match self {
+            Gamut::Srgb => ColorFormat::from_linear_srgb(linear, alpha),
+            Gamut::DisplayP3 => {
+                let [l, c, h] = lab_to_lch(linear_srgb_to_oklab(linear));
+                ColorFormat::Oklch(l, c, h, alpha)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamutMapping {
+    /// Clamps each RGB channel, which is quick but can shift the hue.
+    Clip,
+    /// Lowers the OKLCH chroma at the same lightness and hue until the color fits.
+    ReduceChroma,
+}
+
+impl GamutMapping {
+    pub const ALL: [GamutMapping; 2] = [GamutMapping::Clip, GamutMapping::ReduceChroma];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GamutMapping::Clip => "Clip",
+            GamutMapping::ReduceChroma => "Reduce chroma",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        for color in ["#f43f5e", "#000000", "#ffffff", "hsl(120 100% 50%)"] {
+            let color = ColorFormat::parse(color).unwrap();
+            assert!(Gamut::Srgb.contains(color), "{:?}", color);
+            assert!(Gamut::DisplayP3.contains(color), "{:?}", color);
+        }
+        // vivid, but inside P3
+        let p3_red = ColorFormat::Oklch(0.65, 0.28, 29., 1.);
+        assert!(!Gamut::Srgb.contains(p3_red));
+        assert!(Gamut::DisplayP3.contains(p3_red));
+        let too_green = ColorFormat::Oklch(0.8, 0.4, 145., 1.);
+        assert!(!Gamut::DisplayP3.contains(too_green));
+    }
+
+    #[test]
+    fn map() {
+        let color = ColorFormat::Oklch(0.8, 0.4, 145., 0.5);
+        for gamut in Gamut::ALL {
+            for mapping in GamutMapping::ALL {
+                let mapped = gamut.map(color, mapping);
+                assert!(gamut.contains(mapped), "{:?} {:?}", gamut, mapping);
+                assert!((mapped.alpha() - 0.5).abs() < 0.01);
+            }
+        }
+        assert!(matches!(
+            Gamut::Srgb.map(color, GamutMapping::Clip),
+            ColorFormat::Rgba(..)
+        ));
+
+        // reducing chroma keeps the lightness and the hue
+        let mapped = Gamut::DisplayP3.map(color, GamutMapping::ReduceChroma);
+        let (l, c, h) = mapped.split_oklch();
+        assert!(
+            (l - 0.8).abs() < 0.001 && (h - 145.).abs() < 0.1,
+            "{:?}",
+            mapped
+        );
+        assert!(c < 0.4);
+        let (l, _, h) = Gamut::Srgb
+            .map(color, GamutMapping::ReduceChroma)
+            .split_oklch();
+        assert!(
+            (l - 0.8).abs() < 0.01 && (h - 145.).abs() < 2.,
+            "{} {}",
+            l,
+            h
+        );
+
+        let inside = ColorFormat::parse("#f43f5e").unwrap();
+        assert_eq!(Gamut::Srgb.map(inside, GamutMapping::Clip), inside);
+    }
+}
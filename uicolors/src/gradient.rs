@@ -0,0 +1,414 @@
+// Gradients of several color stops, interpolated in a chosen color space, and their CSS.
+
+use crate::{ColorFormat, Gamut, GamutMapping};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Srgb,
+    LinearSrgb,
+    Oklab,
+    OklchShorter, // hue goes the short way around
+    OklchLonger,  // hue goes the long way around, through more colors
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 5] = [
+        Interpolation::Srgb,
+        Interpolation::LinearSrgb,
+        Interpolation::Oklab,
+        Interpolation::OklchShorter,
+        Interpolation::OklchLonger,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Interpolation::Srgb => "sRGB",
+            Interpolation::LinearSrgb => "Linear",
+            Interpolation::Oklab => "OKLab",
+            Interpolation::OklchShorter => "OKLCH",
+            Interpolation::OklchLonger => "OKLCH longer",
+        }
+    }
+
+    // CSS Color 4 <color-interpolation-method>; sRGB is the default, left out for older browsers
+    fn css(self) -> Option<&'static str> {
+        match self {
+            Interpolation::Srgb => None,
+            Interpolation::LinearSrgb => Some("in srgb-linear"),
+            Interpolation::Oklab => Some("in oklab"),
+            Interpolation::OklchShorter => Some("in oklch"),
+            Interpolation::OklchLonger => Some("in oklch longer hue"),
+        }
+    }
+
+    fn coords(self, color: ColorFormat) -> [f32; 3] {
+        match self {
+            Interpolation::Srgb => color.to_srgb(),
+            Interpolation::LinearSrgb => color.to_linear_srgb(),
+            Interpolation::Oklab => {
+                let (l, a, b) = color.split_oklab();
+                [l, a, b]
+            }
+            Interpolation::OklchShorter | Interpolation::OklchLonger => {
+                let (l, c, h) = color.split_oklch();
+                [l, c, h]
+            }
+        }
+    }
+
+    fn color(self, [x, y, z]: [f32; 3], alpha: f32) -> ColorFormat {
+        match self {
+            Interpolation::Srgb => ColorFormat::from_srgb([x, y, z], alpha),
+            Interpolation::LinearSrgb => ColorFormat::from_linear_srgb([x, y, z], alpha),
+            Interpolation::Oklab => ColorFormat::Oklab(x, y, z, alpha),
+            Interpolation::OklchShorter | Interpolation::OklchLonger => {
+                ColorFormat::Oklch(x, y, z.rem_euclid(360.), alpha)
+            }
+        }
+    }
+
+    /// The color `t` (0..1) of the way from `a` to `b`. Like CSS, channels are mixed
+    /// premultiplied by alpha, and gray colors take the hue of the other color.
+    pub fn mix(self, a: ColorFormat, b: ColorFormat, t: f32) -> ColorFormat {
+        let (alpha0, alpha1) = (a.alpha(), b.alpha());
+        let (mut c0, mut c1) = (self.coords(a), self.coords(b));
+        let polar = matches!(
+            self,
+            Interpolation::OklchShorter | Interpolation::OklchLonger
+        );
+        // the hue is not premultiplied
+        let premultiplied = if polar { 2 } else { 3 };
+        if polar {
+            const GRAY: f32 = 1e-4;
+            if c0[1] < GRAY {
+                c0[2] = c1[2];
+            } else if c1[1] < GRAY {
+                c1[2] = c0[2];
+            }
+            let d = c1[2] - c0[2];
+            c1[2] = match self {
+                Interpolation::OklchShorter if d > 180. => c1[2] - 360.,
+                Interpolation::OklchShorter if d < -180. => c1[2] + 360.,
+                Interpolation::OklchLonger if d > 0. && d < 180. => c1[2] - 360.,
+                Interpolation::OklchLonger if d <= 0. && d > -180. => c1[2] + 360.,
+                _ => c1[2],
+            };
+        }
+        let alpha = alpha0 + (alpha1 - alpha0) * t;
+        let coords = [0, 1, 2].map(|i| {
+            if i < premultiplied {
+                let (x0, x1) = (c0[i] * alpha0, c1[i] * alpha1);
+                let x = x0 + (x1 - x0) * t;
+                if alpha > 0. {
+                    x / alpha
+                } else {
+                    0.
+                }
+            } else {
+                c0[i] + (c1[i] - c0[i]) * t
+            }
+        });
+        self.color(coords, alpha)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientShape {
+    Linear,
+    Radial,
+}
+
+impl GradientShape {
+    pub const ALL: [GradientShape; 2] = [GradientShape::Linear, GradientShape::Radial];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GradientShape::Linear => "Linear",
+            GradientShape::Radial => "Radial",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    pub color: ColorFormat,
+    pub position: f32, // 0..1
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<GradientStop>, // sorted by position, at least two
+    pub interpolation: Interpolation,
+    pub shape: GradientShape,
+    pub angle: f32, // degrees, as in CSS: 0 points up and 90 to the right
+}
+
+impl Gradient {
+    pub fn new(from: ColorFormat, to: ColorFormat) -> Self {
+        Self {
+            stops: vec![
+                GradientStop {
+                    color: from,
+                    position: 0.,
+                },
+                GradientStop {
+                    color: to,
+                    position: 1.,
+                },
+            ],
+            interpolation: Interpolation::Oklab,
+            shape: GradientShape::Linear,
+            angle: 90.,
+        }
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    // keeps the stops in order, returning where the stop at `index` ended up
+    fn sort_stops(&mut self, index: usize) -> usize {
+        let stop = self.stops[index];
+        self.stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        self.stops.iter().position(|x| *x == stop).unwrap_or(index)
+    }
+
+    /// Adds a stop of the gradient's own color at `position`, so that it looks the same until
+    /// the stop is edited. Returns its index.
+    pub fn add_stop(&mut self, position: f32) -> usize {
+        let position = position.clamp(0., 1.);
+        let color = self.at(position);
+        self.stops.push(GradientStop { color, position });
+        self.sort_stops(self.stops.len() - 1)
+    }
+
+    /// Removes a stop, unless only two are left.
+    pub fn remove_stop(&mut self, index: usize) -> bool {
+        if self.stops.len() <= 2 || index >= self.stops.len() {
+            return false;
+        }
+        self.stops.remove(index);
+        true
+    }
+
+    pub fn set_stop_color(&mut self, index: usize, color: ColorFormat) {
+        if let Some(stop) = self.stops.get_mut(index) {
+            stop.color = color;
+        }
+    }
+
+    /// Moves a stop, returning its index in the reordered stops.
+    pub fn set_stop_position(&mut self, index: usize, position: f32) -> usize {
+        let Some(stop) = self.stops.get_mut(index) else {
+            return index;
+        };
+        stop.position = position.clamp(0., 1.);
+        self.sort_stops(index)
+    }
+
+    /// The color at `t` (0..1) along the gradient, in the interpolation space.
+    pub fn at(&self, t: f32) -> ColorFormat {
+        let (first, last) = (self.stops[0], self.stops[self.stops.len() - 1]);
+        if t <= first.position {
+            return first.color;
+        }
+        if t >= last.position {
+            return last.color;
+        }
+        let i = self
+            .stops
+            .iter()
+            .rposition(|x| x.position <= t)
+            .unwrap_or(0);
+        let (a, b) = (self.stops[i], self.stops[i + 1]);
+        let span = b.position - a.position;
+        let u = if span > 0. {
+            (t - a.position) / span
+        } else {
+            1.
+        };
+        self.interpolation.mix(a.color, b.color, u)
+    }
+
+    /// How far along the gradient the point (x, y) of a w × h box is, following CSS: linear
+    /// gradients run along the angle and just reach the corners, radial ones are circles from
+    /// the center to the farthest corner.
+    pub fn position_at(&self, x: f32, y: f32, w: f32, h: f32) -> f32 {
+        let (dx, dy) = (x - w / 2., y - h / 2.);
+        let t = match self.shape {
+            GradientShape::Linear => {
+                let (sin, cos) = self.angle.to_radians().sin_cos();
+                let length = (w * sin).abs() + (h * cos).abs();
+                if length > 0. {
+                    (dx * sin - dy * cos) / length + 0.5
+                } else {
+                    0.
+                }
+            }
+            GradientShape::Radial => {
+                let radius = (w * w + h * h).sqrt() / 2.;
+                if radius > 0. {
+                    (dx * dx + dy * dy).sqrt() / radius
+                } else {
+                    0.
+                }
+            }
+        };
+        t.clamp(0., 1.)
+    }
+
+    /// `n` evenly spaced colors from start to end, brought into sRGB.
+    pub fn steps(&self, n: usize) -> Vec<ColorFormat> {
+        let n = n.max(2);
+        (0..n)
+            .map(|i| {
+                let color = self.at(i as f32 / (n - 1) as f32);
+                Gamut::Srgb.map(color, GamutMapping::ReduceChroma).to_rgb()
+            })
+            .collect()
+    }
+
+    /// CSS `linear-gradient()` or `radial-gradient()`.
+    pub fn to_css(&self) -> String {
+        let mut args = match self.shape {
+            GradientShape::Linear => format!("{}deg", self.angle.round()),
+            GradientShape::Radial => "circle".to_owned(),
+        };
+        if let Some(method) = self.interpolation.css() {
+            args.push(' ');
+            args.push_str(method);
+        }
+        for stop in &self.stops {
+            // HSL is written as h,s,l and HCT as hct(), which are not CSS. Both are sRGB colors,
+            // so hex loses nothing
+            let color = match stop.color {
+                ColorFormat::Hsl(..) | ColorFormat::Hsla(..) | ColorFormat::Hct(..) => {
+                    stop.color.to_rgb()
+                }
+                color => color,
+            };
+            let position = (stop.position * 1000.).round() / 10.;
+            args.push_str(&format!(", {} {}%", color, position));
+        }
+        let function = match self.shape {
+            GradientShape::Linear => "linear-gradient",
+            GradientShape::Radial => "radial-gradient",
+        };
+        format!("{}({})", function, args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interpolation() {
+        let (black, white) = (ColorFormat::Rgb(0, 0, 0), ColorFormat::Rgb(255, 255, 255));
+        let mix = |interpolation: Interpolation| interpolation.mix(black, white, 0.5).to_rgb();
+        assert_eq!(mix(Interpolation::Srgb), ColorFormat::Rgb(128, 128, 128));
+        assert_eq!(
+            mix(Interpolation::LinearSrgb),
+            ColorFormat::Rgb(188, 188, 188)
+        );
+        // gray ends don't bring in a hue
+        let (_, c, _) = Interpolation::OklchShorter
+            .mix(black, white, 0.5)
+            .split_oklch();
+        assert!(c < 1e-3);
+
+        // red (29°) to blue (264°): the short way passes through purple, the long way through
+        // yellow and green
+        let (red, blue) = (ColorFormat::Rgb(255, 0, 0), ColorFormat::Rgb(0, 0, 255));
+        let hue = |interpolation: Interpolation| {
+            let (_, _, h) = interpolation.mix(red, blue, 0.5).split_oklch();
+            h
+        };
+        assert!(
+            (hue(Interpolation::OklchShorter) - 326.6).abs() < 1.,
+            "{}",
+            hue(Interpolation::OklchShorter)
+        );
+        assert!(
+            (hue(Interpolation::OklchLonger) - 146.6).abs() < 1.,
+            "{}",
+            hue(Interpolation::OklchLonger)
+        );
+
+        // transparent ends fade without darkening the other color
+        let clear = ColorFormat::Rgba(0, 0, 0, 0);
+        let mixed = Interpolation::Srgb.mix(white, clear, 0.5);
+        assert_eq!(mixed, ColorFormat::Rgba(255, 255, 255, 128));
+    }
+
+    #[test]
+    fn stops() {
+        let (red, blue) = (ColorFormat::Rgb(255, 0, 0), ColorFormat::Rgb(0, 0, 255));
+        let mut gradient = Gradient::new(red, blue);
+        gradient.interpolation = Interpolation::Srgb;
+        assert_eq!(gradient.at(0.), red);
+        assert_eq!(gradient.at(1.), blue);
+
+        let middle = gradient.add_stop(0.5);
+        assert_eq!(middle, 1);
+        assert_eq!(gradient.stops()[1].color, ColorFormat::Rgb(128, 0, 128));
+        gradient.set_stop_color(middle, ColorFormat::Rgb(0, 255, 0));
+        assert_eq!(gradient.at(0.5), ColorFormat::Rgb(0, 255, 0));
+        assert_eq!(gradient.at(0.25), ColorFormat::Rgb(128, 128, 0));
+
+        // moving a stop past another reorders them
+        assert_eq!(gradient.set_stop_position(0, 0.75), 1);
+        assert_eq!(gradient.stops()[0].color, ColorFormat::Rgb(0, 255, 0));
+        assert!(gradient.remove_stop(0));
+        assert!(!gradient.remove_stop(0));
+        assert_eq!(gradient.stops().len(), 2);
+    }
+
+    #[test]
+    fn export() {
+        let mut gradient = Gradient::new(
+            ColorFormat::Rgb(0xf4, 0x3f, 0x5e),
+            ColorFormat::Rgb(0x3b, 0x82, 0xf6),
+        );
+        assert_eq!(
+            gradient.to_css(),
+            "linear-gradient(90deg in oklab, #f43f5e 0%, #3b82f6 100%)"
+        );
+        gradient.add_stop(0.333);
+        gradient.set_stop_color(1, ColorFormat::Hsl(120., 50., 40.));
+        gradient.add_stop(0.667);
+        gradient.set_stop_color(2, ColorFormat::Hct(0., 0., 50., 1.));
+        gradient.shape = GradientShape::Radial;
+        gradient.interpolation = Interpolation::OklchLonger;
+        assert_eq!(
+            gradient.to_css(),
+            "radial-gradient(circle in oklch longer hue, #f43f5e 0%, #339933 33.3%, #777777 66.7%, #3b82f6 100%)"
+        );
+
+        let steps = gradient.steps(5);
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[0], ColorFormat::Rgb(0xf4, 0x3f, 0x5e));
+        assert_eq!(steps[4], ColorFormat::Rgb(0x3b, 0x82, 0xf6));
+        assert!(steps.iter().all(|x| matches!(x, ColorFormat::Rgb(..))));
+    }
+
+    #[test]
+    fn geometry() {
+        let mut gradient =
+            Gradient::new(ColorFormat::Rgb(0, 0, 0), ColorFormat::Rgb(255, 255, 255));
+        // 90deg runs left to right
+        assert_eq!(gradient.position_at(0., 50., 200., 100.), 0.);
+        assert_eq!(gradient.position_at(100., 0., 200., 100.), 0.5);
+        assert_eq!(gradient.position_at(200., 50., 200., 100.), 1.);
+        // 180deg runs top to bottom
+        gradient.angle = 180.;
+        assert!((gradient.position_at(0., 25., 200., 100.) - 0.25).abs() < 1e-5);
+        // 45deg reaches exactly the corners
+        gradient.angle = 45.;
+        assert!(gradient.position_at(0., 100., 200., 100.).abs() < 1e-5);
+        assert!((gradient.position_at(200., 0., 200., 100.) - 1.).abs() < 1e-5);
+        gradient.shape = GradientShape::Radial;
+        assert_eq!(gradient.position_at(100., 50., 200., 100.), 0.);
+        assert!((gradient.position_at(0., 0., 200., 100.) - 1.).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,92 @@
+// Color harmonies derived by rotating the OKLCH hue of a base color. Lightness and chroma are kept,
+// so the related colors look as light and as colorful as the base, unlike HSL hue rotation.
+
+use crate::{colorspace::*, ColorFormat};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Harmony {
+    Complementary,
+    Triadic,
+    Analogous,
+    SplitComplementary,
+    Tetradic,
+}
+
+impl Harmony {
+    pub const ALL: [Harmony; 5] = [
+        Harmony::Complementary,
+        Harmony::Triadic,
+        Harmony::Analogous,
+        Harmony::SplitComplementary,
+        Harmony::Tetradic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Harmony::Complementary => "Complementary",
+            Harmony::Triadic => "Triadic",
+            Harmony::Analogous => "Analogous",
+            Harmony::SplitComplementary => "Split",
+            Harmony::Tetradic => "Tetradic",
+        }
+    }
+
+    /// Hue offsets in degrees of the related colors, not including the base color itself.
+    pub fn offsets(self) -> &'static [f32] {
+        match self {
+            Harmony::Complementary => &[180.],
+            Harmony::Triadic => &[120., 240.],
+            Harmony::Analogous => &[-30., 30.],
+            Harmony::SplitComplementary => &[150., 210.],
+            Harmony::Tetradic => &[90., 180., 270.],
+        }
+    }
+
+    /// The related colors of `color`, brought into sRGB by reducing chroma where needed.
+    pub fn colors(self, color: ColorFormat) -> Vec<ColorFormat> {
+        let (l, c, h) = color.split_oklch();
+        self.offsets()
+            .iter()
+            .map(|offset| {
+                let rgb = oklch_to_srgb_gamut_mapped([l, c, (h + offset).rem_euclid(360.)]);
+                ColorFormat::from_srgb(rgb, color.alpha())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hue_distance(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(360.);
+        d.min(360. - d)
+    }
+
+    #[test]
+    fn colors() {
+        let base = ColorFormat::Rgb(0x3b, 0x82, 0xf6);
+        let (l, _, h) = base.split_oklch();
+        for harmony in Harmony::ALL {
+            let colors = harmony.colors(base);
+            assert_eq!(colors.len(), harmony.offsets().len());
+            for (color, offset) in colors.iter().zip(harmony.offsets()) {
+                let (l1, _, h1) = color.split_oklch();
+                assert!((l1 - l).abs() < 0.01, "{:?} {}", harmony, color);
+                // chroma reduction and 8-bit rounding move the hue a little
+                let expected = (h + offset).rem_euclid(360.);
+                assert!(hue_distance(h1, expected) < 3., "{:?} {}", harmony, color);
+            }
+        }
+    }
+
+    #[test]
+    fn keep_alpha() {
+        let base = ColorFormat::Rgba(0xef, 0x44, 0x44, 128);
+        let [complement] = Harmony::Complementary.colors(base)[..] else {
+            panic!("expected one color");
+        };
+        assert_eq!(complement.alpha(), base.alpha());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::ColorFormat;
+
+/// Undo history of the edited color. Every change made during a gesture, like dragging a slider,
+/// replaces the entry the gesture started, so that one undo goes back to before the drag.
+pub struct ColorHistory {
+    entries: Vec<ColorFormat>,
+    cursor: usize,
+    gesture: Option<bool>, // Some(pushed) while a gesture is in progress
+}
+
+impl ColorHistory {
+    const LIMIT: usize = 200;
+
+    pub fn new(color: ColorFormat) -> Self {
+        Self {
+            entries: vec![color],
+            cursor: 0,
+            gesture: None,
+        }
+    }
+
+    pub fn current(&self) -> ColorFormat {
+        self.entries[self.cursor]
+    }
+
+    pub fn push(&mut self, color: ColorFormat) {
+        if color == self.current() {
+            return;
+        }
+        self.entries.truncate(self.cursor + 1);
+        if self.gesture == Some(true) {
+            self.entries[self.cursor] = color;
+            return;
+        }
+        self.entries.push(color);
+        if self.entries.len() > Self::LIMIT {
+            self.entries.remove(0);
+        }
+        self.cursor = self.entries.len() - 1;
+        if self.gesture.is_some() {
+            self.gesture = Some(true);
+        }
+    }
+
+    pub fn begin_gesture(&mut self) {
+        self.gesture = Some(false);
+    }
+
+    pub fn end_gesture(&mut self) {
+        self.gesture = None;
+    }
+
+    /// Starts over from the color, e.g. when another palette becomes the edited one.
+    pub fn reset(&mut self, color: ColorFormat) {
+        *self = Self::new(color);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor + 1 < self.entries.len()
+    }
+
+    pub fn undo(&mut self) -> Option<ColorFormat> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.gesture = None;
+        self.cursor -= 1;
+        Some(self.current())
+    }
+
+    pub fn redo(&mut self) -> Option<ColorFormat> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.gesture = None;
+        self.cursor += 1;
+        Some(self.current())
+    }
+
+    /// Distinct colors up to the current one, most recent first.
+    pub fn recent(&self, n: usize) -> Vec<ColorFormat> {
+        let mut colors: Vec<ColorFormat> = vec![];
+        for color in self.entries[..=self.cursor].iter().rev() {
+            if !colors.contains(color) {
+                colors.push(*color);
+                if colors.len() == n {
+                    break;
+                }
+            }
+        }
+        colors
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let (a, b, c) = (
+            ColorFormat::Hsl(0., 50., 50.),
+            ColorFormat::Hsl(120., 50., 50.),
+            ColorFormat::Hsl(240., 50., 50.),
+        );
+        let mut history = ColorHistory::new(a);
+        assert_eq!(history.undo(), None);
+        history.push(b);
+        history.push(b);
+        history.push(c);
+        assert_eq!(history.undo(), Some(b));
+        assert_eq!(history.undo(), Some(a));
+        assert_eq!(history.redo(), Some(b));
+        assert_eq!(history.recent(5), [b, a]);
+
+        // a new color drops the redo entries
+        history.push(a);
+        assert!(!history.can_redo());
+        assert_eq!(history.recent(5), [a, b]);
+        assert_eq!(history.undo(), Some(b));
+        assert_eq!(history.undo(), Some(a));
+        assert_eq!(history.undo(), None);
+
+        history.reset(c);
+        assert_eq!(history.current(), c);
+        assert!(!history.can_undo() && !history.can_redo());
+    }
+
+    #[test]
+    fn gesture() {
+        let mut history = ColorHistory::new(ColorFormat::Hsl(0., 50., 50.));
+        history.begin_gesture();
+        for h in 1..60 {
+            history.push(ColorFormat::Hsl(h as f32, 50., 50.));
+        }
+        history.end_gesture();
+        history.push(ColorFormat::Hsl(200., 50., 50.));
+        assert_eq!(history.undo(), Some(ColorFormat::Hsl(59., 50., 50.)));
+        assert_eq!(history.undo(), Some(ColorFormat::Hsl(0., 50., 50.)));
+        assert_eq!(history.undo(), None);
+
+        // a gesture that doesn't change the color adds nothing
+        history.begin_gesture();
+        history.end_gesture();
+        assert!(history.can_redo());
+    }
+}
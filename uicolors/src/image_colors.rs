@@ -0,0 +1,176 @@
+// Colors picked out of images: loading them from files or the clipboard, and finding the few colors
+// an image is mostly made of.
+
+use crate::{colorspace::*, ColorFormat};
+use anyhow::{anyhow, Context, Result};
+use image::RgbaImage;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::path::Path;
+
+pub fn load_image(path: &Path) -> Result<RgbaImage> {
+    let image = image::open(path).with_context(|| format!("reading {}", path.display()))?;
+    Ok(image.to_rgba8())
+}
+
+/// The image on the clipboard, e.g. a screenshot taken to the clipboard.
+pub fn clipboard_image() -> Result<RgbaImage> {
+    let mut clipboard = arboard::Clipboard::new()?;
+    let data = clipboard
+        .get_image()
+        .map_err(|_| anyhow!("there is no image on the clipboard"))?;
+    RgbaImage::from_raw(
+        data.width as u32,
+        data.height as u32,
+        data.bytes.into_owned(),
+    )
+    .ok_or_else(|| anyhow!("unsupported clipboard image"))
+}
+
+pub fn pixel_color(image: &RgbaImage, x: u32, y: u32) -> Option<ColorFormat> {
+    if x >= image.width() || y >= image.height() {
+        return None;
+    }
+    let [r, g, b, a] = image.get_pixel(x, y).0;
+    Some(ColorFormat::Rgba(r, g, b, a).opaque_if_possible())
+}
+
+/// Up to `k` colors the image is mostly made of, with the share of the pixels close to each, most
+/// common first. Clusters pixels with k-means in OKLab, so that similar looking colors go
+/// together. Mostly transparent pixels are ignored.
+pub fn dominant_colors(image: &RgbaImage, k: usize) -> Vec<(ColorFormat, f32)> {
+    const MAX_SAMPLES: u32 = 10_000;
+    const ITERATIONS: usize = 20;
+    // clusters closer than this are the same color
+    const MIN_DISTANCE: f32 = 0.04;
+
+    let (w, h) = image.dimensions();
+    let pixels = w as usize * h as usize; // u32 overflows for huge images
+    let step = ((pixels as f32 / MAX_SAMPLES as f32).sqrt().ceil() as u32).max(1);
+    let samples: Vec<Vec3> = (0..h)
+        .step_by(step as usize)
+        .flat_map(|y| (0..w).step_by(step as usize).map(move |x| (x, y)))
+        .filter_map(|(x, y)| {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            let rgb = [r, g, b].map(|c| c as f32 / 255.);
+            (a >= 128).then(|| linear_srgb_to_oklab(srgb_to_linear_srgb(rgb)))
+        })
+        .collect();
+    if samples.is_empty() || k == 0 {
+        return vec![];
+    }
+
+    let distance = |a: Vec3, b: Vec3| {
+        let d = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        d[0] * d[0] + d[1] * d[1] + d[2] * d[2]
+    };
+    let nearest = |centers: &[Vec3], p: Vec3| -> (usize, f32) {
+        centers
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, distance(*c, p)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+    };
+
+    // k-means++ seeding, with a fixed seed so that the same image gives the same colors
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut centers = vec![samples[rng.gen_range(0..samples.len())]];
+    while centers.len() < k {
+        let weights: Vec<f32> = samples.iter().map(|p| nearest(&centers, *p).1).collect();
+        let total: f32 = weights.iter().sum();
+        if total <= 0. {
+            break; // fewer distinct colors than k
+        }
+        let mut target = rng.gen::<f32>() * total;
+        let i = weights
+            .iter()
+            .position(|w| {
+                target -= w;
+                target <= 0.
+            })
+            .unwrap_or(samples.len() - 1);
+        centers.push(samples[i]);
+    }
+
+    let mut assignment = vec![usize::MAX; samples.len()];
+    for _ in 0..ITERATIONS {
+        let mut changed = false;
+        for (p, a) in samples.iter().zip(assignment.iter_mut()) {
+            let (i, _) = nearest(&centers, *p);
+            changed |= *a != i;
+            *a = i;
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![([0f32; 3], 0usize); centers.len()];
+        for (p, a) in samples.iter().zip(&assignment) {
+            let (sum, n) = &mut sums[*a];
+            (0..3).for_each(|j| sum[j] += p[j]);
+            *n += 1;
+        }
+        for (center, (sum, n)) in centers.iter_mut().zip(sums) {
+            if n > 0 {
+                *center = sum.map(|x| x / n as f32);
+            }
+        }
+    }
+
+    let mut clusters: Vec<(Vec3, usize)> = centers.iter().map(|c| (*c, 0)).collect();
+    for a in &assignment {
+        clusters[*a].1 += 1;
+    }
+    clusters.sort_by_key(|x| std::cmp::Reverse(x.1));
+    let mut kept: Vec<(Vec3, usize)> = vec![];
+    for (center, n) in clusters.into_iter().filter(|(_, n)| *n > 0) {
+        let close = kept
+            .iter_mut()
+            .find(|(x, _)| distance(*x, center) < MIN_DISTANCE * MIN_DISTANCE);
+        match close {
+            Some((_, kept_n)) => *kept_n += n,
+            None => kept.push((center, n)),
+        }
+    }
+    kept.into_iter()
+        .map(|(center, n)| {
+            let color = ColorFormat::from_linear_srgb(oklab_to_linear_srgb(center), 1.);
+            (color, n as f32 / samples.len() as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn dominant() {
+        // 60% red, 30% blue, 10% a slightly different blue
+        let image = RgbaImage::from_fn(100, 50, |x, _| match x {
+            0..=59 => Rgba([220, 38, 38, 255]),
+            60..=89 => Rgba([37, 99, 235, 255]),
+            _ => Rgba([40, 100, 230, 255]),
+        });
+        let colors = dominant_colors(&image, 4);
+        assert_eq!(colors.len(), 2, "{:?}", colors);
+        assert_eq!(colors[0].0, ColorFormat::Rgb(220, 38, 38));
+        assert!((colors[0].1 - 0.6).abs() < 0.02, "{:?}", colors);
+        let (_, _, b) = colors[1].0.split_rgb();
+        assert!(b > 220, "{:?}", colors);
+
+        let transparent = RgbaImage::from_pixel(10, 10, Rgba([255, 0, 0, 0]));
+        assert!(dominant_colors(&transparent, 4).is_empty());
+    }
+
+    #[test]
+    fn pixel() {
+        let image = RgbaImage::from_fn(2, 1, |x, _| Rgba([x as u8, 0, 0, 128 + x as u8 * 127]));
+        assert_eq!(
+            pixel_color(&image, 0, 0),
+            Some(ColorFormat::Rgba(0, 0, 0, 128))
+        );
+        assert_eq!(pixel_color(&image, 1, 0), Some(ColorFormat::Rgb(1, 0, 0)));
+        assert_eq!(pixel_color(&image, 2, 0), None);
+    }
+}
@@ -0,0 +1,589 @@
+// Importers turning existing color definitions into library items: color objects of a
+// tailwind.config.js, CSS custom properties, and W3C Design Tokens JSON. Scales like
+// `blue.50` .. `blue.950` are grouped into one item per family.
+
+use crate::{ColorFormat, ColorItem};
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    Tailwind,
+    Css,
+    DesignTokens,
+}
+
+impl ImportFormat {
+    /// Guesses the format from the file extension, then from the content.
+    pub fn detect(path: &Path, src: &str) -> Option<Self> {
+        let ext = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+        match ext.to_lowercase().as_str() {
+            "js" | "cjs" | "mjs" | "ts" => return Some(ImportFormat::Tailwind),
+            "css" | "scss" | "less" => return Some(ImportFormat::Css),
+            "json" => return Some(ImportFormat::DesignTokens),
+            _ => {}
+        }
+        let src = src.trim_start();
+        if src.starts_with('{') {
+            Some(ImportFormat::DesignTokens)
+        } else if src.contains("colors") {
+            Some(ImportFormat::Tailwind)
+        } else if src.contains("--") {
+            Some(ImportFormat::Css)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ImportReport {
+    pub items: Vec<ColorItem>,
+    pub skipped: Vec<(String, String)>, // (entry, reason)
+}
+
+pub fn import_file(path: &Path) -> Result<ImportReport> {
+    let src = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let format = ImportFormat::detect(path, &src)
+        .ok_or_else(|| anyhow!("unknown file format: {}", path.display()))?;
+    import(format, &src)
+}
+
+/// Only fails when the whole file can't be read, e.g. invalid JSON. Entries that aren't colors
+/// are listed in `ImportReport::skipped`.
+pub fn import(format: ImportFormat, src: &str) -> Result<ImportReport> {
+    let mut entries = Entries::default();
+    match format {
+        ImportFormat::Tailwind => import_tailwind(src, &mut entries),
+        ImportFormat::Css => import_css(src, &mut entries),
+        ImportFormat::DesignTokens => import_design_tokens(src, &mut entries)?,
+    }
+    Ok(entries.into_report())
+}
+
+#[derive(Default)]
+struct Entries {
+    colors: Vec<(Vec<String>, ColorFormat)>,
+    skipped: Vec<(String, String)>,
+}
+
+impl Entries {
+    fn add(&mut self, path: &[String], value: &str) {
+        match ColorFormat::parse(value) {
+            Ok(color) => self.colors.push((split_shade(path), color)),
+            Err(err) => self.skip(path, format!("\"{}\": {}", value, err)),
+        }
+    }
+
+    fn skip(&mut self, path: &[String], reason: impl Into<String>) {
+        self.skipped.push((path.join("."), reason.into()));
+    }
+
+    fn into_report(self) -> ImportReport {
+        #[derive(Default)]
+        struct Family {
+            default: Option<ColorFormat>,
+            shades: IndexMap<usize, ColorFormat>,
+        }
+
+        let mut families: IndexMap<String, Family> = IndexMap::new();
+        for (path, color) in self.colors {
+            let (last, prefix) = match path.split_last() {
+                Some(x) => x,
+                None => continue,
+            };
+            match last.parse::<usize>() {
+                Ok(shade) if !prefix.is_empty() => {
+                    let family = families.entry(prefix.join("-")).or_default();
+                    family.shades.insert(shade, color);
+                }
+                _ if last == "DEFAULT" && !prefix.is_empty() => {
+                    families.entry(prefix.join("-")).or_default().default = Some(color);
+                }
+                _ => families.entry(path.join("-")).or_default().default = Some(color),
+            }
+        }
+
+        let items = families
+            .into_iter()
+            .filter_map(|(name, mut family)| {
+                family.shades.sort_keys();
+                let color = family
+                    .default
+                    .or_else(|| family.shades.get(&500).copied())
+                    .or_else(|| {
+                        let mid = family.shades.len() / 2;
+                        family.shades.get_index(mid).map(|(_, c)| *c)
+                    })?;
+                let mut item = ColorItem::new(color, Some(name));
+                item.shades = family.shades;
+                Some(item)
+            })
+            .collect();
+        ImportReport {
+            items,
+            skipped: self.skipped,
+        }
+    }
+}
+
+// "blue-500" is the same as a nested "blue" / "500"
+fn split_shade(path: &[String]) -> Vec<String> {
+    let mut path = path.to_vec();
+    if let Some(last) = path.pop() {
+        match last.rsplit_once('-') {
+            Some((name, shade)) if !name.is_empty() && shade.parse::<usize>().is_ok() => {
+                path.push(name.to_owned());
+                path.push(shade.to_owned());
+            }
+            _ => path.push(last),
+        }
+    }
+    path
+}
+
+// Tailwind config: the object literals after `colors:` keys, usually `theme.colors` or
+// `theme.extend.colors`. Anything but string values (imports, functions, spreads) is skipped.
+fn import_tailwind(src: &str, entries: &mut Entries) {
+    let mut parser = JsParser { src, pos: 0 };
+    let mut found = false;
+    while let Some(start) = parser.find_key("colors") {
+        parser.pos = start;
+        if parser.peek() == Some('{') {
+            parser.parse_object(&mut vec![], entries);
+            found = true;
+        }
+    }
+    // a module exporting only colors, e.g. `module.exports = { blue: { ... } }`
+    if !found {
+        if let Some(start) = src.find('{') {
+            parser.pos = start;
+            parser.parse_object(&mut vec![], entries);
+        }
+    }
+}
+
+struct JsParser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn skip_ws(&mut self) {
+        loop {
+            let rest = &self.src[self.pos..];
+            if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                self.pos += rest.find("*/").map_or(rest.len(), |i| i + 2);
+            } else if rest.starts_with(|ch: char| ch.is_whitespace()) {
+                self.bump();
+            } else {
+                return;
+            }
+        }
+    }
+
+    // Moves past the next `key:` outside of strings and comments, returning the position of
+    // its value.
+    fn find_key(&mut self, key: &str) -> Option<usize> {
+        while self.pos < self.src.len() {
+            self.skip_ws();
+            match self.peek()? {
+                '"' | '\'' | '`' => {
+                    self.parse_string();
+                }
+                ch if is_ident_char(ch) => {
+                    let ident = self.parse_ident();
+                    if ident == key {
+                        self.skip_ws();
+                        if self.peek() == Some(':') {
+                            self.bump();
+                            self.skip_ws();
+                            return Some(self.pos);
+                        }
+                    }
+                }
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        None
+    }
+
+    fn parse_ident(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_ident_char) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn parse_string(&mut self) -> String {
+        let quote = self.bump().unwrap_or('"');
+        let mut s = String::new();
+        while let Some(ch) = self.bump() {
+            match ch {
+                '\\' => s.extend(self.bump()),
+                ch if ch == quote => break,
+                ch => s.push(ch),
+            }
+        }
+        s
+    }
+
+    // skips an expression we can't evaluate, up to the next `,` or `}` at the same depth
+    fn skip_expr(&mut self) -> &'a str {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(ch) = self.peek() {
+            match ch {
+                '"' | '\'' | '`' => {
+                    self.parse_string();
+                    continue;
+                }
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth > 0 => depth -= 1,
+                ',' | '}' if depth == 0 => break,
+                _ => {}
+            }
+            self.bump();
+        }
+        self.src[start..self.pos].trim()
+    }
+
+    fn parse_object(&mut self, path: &mut Vec<String>, entries: &mut Entries) {
+        self.bump(); // {
+        loop {
+            self.skip_ws();
+            let key = match self.peek() {
+                None => return,
+                Some('}') => {
+                    self.bump();
+                    return;
+                }
+                Some(',') => {
+                    self.bump();
+                    continue;
+                }
+                Some('"' | '\'' | '`') => self.parse_string(),
+                Some('.') => {
+                    let expr = self.skip_expr().to_owned();
+                    entries
+                        .skipped
+                        .push((expr, "unsupported spread".to_owned()));
+                    continue;
+                }
+                Some(ch) if is_ident_char(ch) => self.parse_ident().to_owned(),
+                Some(_) => {
+                    let expr = self.skip_expr();
+                    entries.skip(path, format!("unsupported entry `{}`", expr));
+                    continue;
+                }
+            };
+
+            path.push(key);
+            self.skip_ws();
+            if self.peek() == Some(':') {
+                self.bump();
+                self.skip_ws();
+                match self.peek() {
+                    Some('{') => self.parse_object(path, entries),
+                    Some('"' | '\'' | '`') => {
+                        let value = self.parse_string();
+                        self.skip_ws();
+                        // e.g. 'a' + 'b' or a ternary
+                        if !matches!(self.peek(), Some(',' | '}') | None) {
+                            let expr = self.skip_expr();
+                            entries.skip(path, format!("unsupported value `{}`", expr));
+                        } else {
+                            entries.add(path, &value);
+                        }
+                    }
+                    _ => {
+                        let expr = self.skip_expr();
+                        entries.skip(path, format!("unsupported value `{}`", expr));
+                    }
+                }
+            } else {
+                let expr = self.skip_expr();
+                entries.skip(path, format!("unsupported entry `{}`", expr));
+            }
+            path.pop();
+        }
+    }
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '$' || ch == '-'
+}
+
+// CSS: every `--name: value` declaration, resolving `var(--other)` references.
+fn import_css(src: &str, entries: &mut Entries) {
+    let mut src = src.to_owned();
+    while let Some(start) = src.find("/*") {
+        let end = src[start..].find("*/").map_or(src.len(), |i| start + i + 2);
+        src.replace_range(start..end, " ");
+    }
+
+    let mut vars: IndexMap<String, String> = IndexMap::new();
+    let mut rest = src.as_str();
+    while let Some(i) = rest.find("--") {
+        let before = rest[..i].trim_end();
+        rest = &rest[i + 2..];
+        // only at the start of a declaration, not inside `var(--x)`
+        if !(before.is_empty() || before.ends_with(['{', ';', '}'])) {
+            continue;
+        }
+        let Some(colon) = rest.find(':') else {
+            break;
+        };
+        let name = rest[..colon].trim();
+        if name.is_empty() || !name.chars().all(is_ident_char) {
+            continue;
+        }
+        rest = &rest[colon + 1..];
+        let mut depth = 0;
+        let end = rest
+            .char_indices()
+            .find(|(_, ch)| {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    ';' | '}' if depth <= 0 => return true,
+                    _ => {}
+                }
+                false
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        let value = rest[..end].trim().trim_end_matches("!important").trim();
+        vars.insert(name.to_owned(), value.to_owned());
+        rest = &rest[end..];
+    }
+
+    for (name, value) in &vars {
+        let mut value = value.as_str();
+        // follow var() chains, with a limit in case of cycles
+        for _ in 0..16 {
+            let Some(inner) = value
+                .strip_prefix("var(--")
+                .and_then(|x| x.strip_suffix(')'))
+            else {
+                break;
+            };
+            let inner = inner.split(',').next().unwrap_or("").trim();
+            match vars.get(inner) {
+                Some(x) => value = x,
+                None => break,
+            }
+        }
+        entries.add(std::slice::from_ref(name), value);
+    }
+}
+
+// W3C Design Tokens: tokens are objects with a `$value`, typed by their own or an ancestor
+// group's `$type`. Style Dictionary's `value` / `type` are read too.
+fn import_design_tokens(src: &str, entries: &mut Entries) -> Result<()> {
+    fn collect(
+        value: &Value,
+        path: &mut Vec<String>,
+        ty: Option<&str>,
+        tokens: &mut IndexMap<String, (Vec<String>, Option<String>, Value)>,
+    ) {
+        let Value::Object(obj) = value else {
+            return;
+        };
+        let ty = obj
+            .get("$type")
+            .or_else(|| obj.get("type"))
+            .and_then(|x| x.as_str())
+            .or(ty);
+        if let Some(value) = obj.get("$value").or_else(|| obj.get("value")) {
+            let token = (path.clone(), ty.map(str::to_owned), value.clone());
+            tokens.insert(path.join("."), token);
+            return;
+        }
+        for (key, child) in obj {
+            if key.starts_with('$') {
+                continue;
+            }
+            path.push(key.clone());
+            collect(child, path, ty, tokens);
+            path.pop();
+        }
+    }
+
+    // the W3C color object, e.g. { "colorSpace": "display-p3", "components": [1, 0, 0] }
+    fn color_object(obj: &serde_json::Map<String, Value>) -> Option<String> {
+        if let Some(hex) = obj.get("hex").and_then(|x| x.as_str()) {
+            return Some(hex.to_owned());
+        }
+        let space = obj.get("colorSpace")?.as_str()?;
+        let c: Vec<String> = obj
+            .get("components")?
+            .as_array()?
+            .iter()
+            .map(|x| x.as_f64().map_or("none".to_owned(), |x| x.to_string()))
+            .collect();
+        let alpha = obj.get("alpha").and_then(|x| x.as_f64()).unwrap_or(1.);
+        let args = format!("{} / {}", c.join(" "), alpha);
+        match space {
+            "oklch" | "oklab" | "lab" | "lch" | "hsl" | "hwb" => {
+                Some(format!("{}({})", space, args))
+            }
+            _ => Some(format!("color({} {})", space, args)),
+        }
+    }
+
+    let root: Value = serde_json::from_str(src).context("invalid design tokens JSON")?;
+    let mut tokens = IndexMap::new();
+    collect(&root, &mut vec![], None, &mut tokens);
+
+    for (path, ty, value) in tokens.values() {
+        if ty.as_deref().is_some_and(|ty| ty != "color") {
+            continue;
+        }
+        let mut value = value;
+        // aliases look like "{color.blue.500}"
+        for _ in 0..16 {
+            let Some(alias) = value
+                .as_str()
+                .and_then(|x| x.strip_prefix('{'))
+                .and_then(|x| x.strip_suffix('}'))
+            else {
+                break;
+            };
+            match tokens.get(alias) {
+                Some((_, _, x)) => value = x,
+                None => break,
+            }
+        }
+        match value {
+            Value::String(s) => entries.add(path, s),
+            Value::Object(obj) => match color_object(obj) {
+                Some(s) => entries.add(path, &s),
+                None => entries.skip(path, format!("unsupported value {}", value)),
+            },
+            _ => entries.skip(path, format!("unsupported value {}", value)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(report: &ImportReport) -> Vec<&str> {
+        report
+            .items
+            .iter()
+            .map(|x| x.name.as_deref().unwrap_or(""))
+            .collect()
+    }
+
+    #[test]
+    fn tailwind() {
+        let src = r##"
+            const colors = require('tailwindcss/colors')
+            module.exports = {
+              content: ['./src/**/*.{html,js}'],
+              theme: {
+                extend: {
+                  colors: {
+                    // brand colors
+                    brand: {
+                      50: '#eff6ff',
+                      500: "#3b82f6",
+                      950: 'rgb(23 37 84)',
+                    },
+                    'accent-100': '#fce7f3',
+                    'accent-500': '#ec4899',
+                    gray: colors.slate,
+                    ...colors,
+                    danger: { DEFAULT: '#ef4444', light: '#fca5a5' },
+                    oops: 'not-a-color',
+                  },
+                },
+              },
+            }
+        "##;
+        let report = import(ImportFormat::Tailwind, src).unwrap();
+        assert_eq!(
+            names(&report),
+            ["brand", "accent", "danger", "danger-light"]
+        );
+
+        let brand = &report.items[0];
+        assert_eq!(brand.color, ColorFormat::Rgb(0x3b, 0x82, 0xf6));
+        assert_eq!(
+            brand.shades.keys().copied().collect::<Vec<_>>(),
+            [50, 500, 950]
+        );
+        assert_eq!(brand.shades[&950], ColorFormat::Rgb(23, 37, 84));
+        assert_eq!(report.items[1].shades.len(), 2);
+        assert_eq!(report.items[2].color, ColorFormat::Rgb(0xef, 0x44, 0x44));
+
+        let skipped: Vec<&str> = report.skipped.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(skipped, ["gray", "...colors", "oops"]);
+    }
+
+    #[test]
+    fn css() {
+        let src = r##"
+            /* --commented: #000; */
+            :root {
+              --primary-50: #eff6ff;
+              --primary-500: oklch(0.62 0.19 260);
+              --primary-900: rgb(30, 58, 138);
+              --link: var(--primary-500);
+              --radius: 4px;
+            }
+            .x { color: var(--link); }
+        "##;
+        let report = import(ImportFormat::Css, src).unwrap();
+        assert_eq!(names(&report), ["primary", "link"]);
+        assert_eq!(report.items[0].shades.len(), 3);
+        assert_eq!(report.items[1].color, report.items[0].shades[&500]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, "radius");
+    }
+
+    #[test]
+    fn design_tokens() {
+        let src = r##"{
+            "color": {
+                "$type": "color",
+                "blue": {
+                    "100": { "$value": "#dbeafe" },
+                    "500": { "$value": "#3b82f6" }
+                },
+                "primary": { "$value": "{color.blue.500}" },
+                "p3": { "$value": { "colorSpace": "display-p3", "components": [1, 0, 0] } },
+                "broken": { "$value": "#12" }
+            },
+            "spacing": { "sm": { "$type": "dimension", "$value": "4px" } }
+        }"##;
+        let report = import(ImportFormat::DesignTokens, src).unwrap();
+        assert_eq!(names(&report), ["color-blue", "color-primary", "color-p3"]);
+        assert_eq!(report.items[1].color, ColorFormat::Rgb(0x3b, 0x82, 0xf6));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, "color.broken");
+
+        assert!(import(ImportFormat::DesignTokens, "{ nope").is_err());
+    }
+}
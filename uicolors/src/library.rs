@@ -0,0 +1,339 @@
+use crate::{ColorFormat, CopyFormat, DeltaE};
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ColorItem {
+    pub color: ColorFormat,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    // exact 50..950 scale of imported color families, empty for single colors
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub shades: IndexMap<usize, ColorFormat>,
+}
+
+impl ColorItem {
+    pub fn new(color: ColorFormat, name: Option<String>) -> Self {
+        Self {
+            color,
+            name,
+            favorite: false,
+            shades: IndexMap::new(),
+        }
+    }
+}
+
+/// A library written by a newer version of the app. It must not be overwritten, as saving it
+/// with this version's schema would lose what the newer one added.
+#[derive(Debug)]
+pub struct NewerVersion(pub u32);
+
+impl std::fmt::Display for NewerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "library version {} is newer than supported version {}",
+            self.0,
+            ColorLibrary::VERSION
+        )
+    }
+}
+
+impl std::error::Error for NewerVersion {}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ColorLibrary {
+    // files written before versioning have no version field, and are read as version 0
+    #[serde(default)]
+    pub version: u32,
+    pub items: Vec<ColorItem>,
+    // what clicking a palette swatch copies, kept here as this is the file that outlives a launch
+    #[serde(default)]
+    pub copy_format: CopyFormat,
+}
+
+impl Default for ColorLibrary {
+    fn default() -> Self {
+        fn item(hex: &str, name: &str) -> ColorItem {
+            ColorItem::new(hex.into(), Some(name.to_owned()))
+        }
+
+        Self {
+            version: Self::VERSION,
+            items: vec![
+                item("#f43f5e", "Rose"),
+                item("#ec4899", "Pink"),
+                item("#d946ef", "Fuchsia"),
+                item("#a855f7", "Purple"),
+                item("#8b5cf6", "Violet"),
+                item("#6366f1", "Indigo"),
+                item("#3b82f6", "Blue"),
+                item("#0ea5e9", "Sky"),
+                item("#06b6d4", "Cyan"),
+                item("#14b8a6", "Teal"),
+                item("#10b981", "Emerald"),
+                item("#22c55e", "Green"),
+                item("#84cc16", "Lime"),
+                item("#eab308", "Yellow"),
+                item("#f59e0b", "Amber"),
+                item("#f97316", "Orange"),
+                item("#ef4444", "Red"),
+                item("#78716c", "Stone"),
+                item("#737373", "Neutral"),
+            ],
+            copy_format: CopyFormat::default(),
+        }
+    }
+}
+
+impl ColorLibrary {
+    /// Schema version written by this build. Bump it and extend `migrate` when the format changes.
+    pub const VERSION: u32 = 1;
+
+    /// The item that looks the same as this color, whatever format either is written in: the
+    /// closest one within a just noticeable CIEDE2000 difference.
+    pub fn position(&self, color: ColorFormat) -> Option<usize> {
+        let metric = DeltaE::Ciede2000;
+        let (index, distance) = self.nearest(color, metric)?;
+        (distance < metric.just_noticeable()).then_some(index)
+    }
+
+    /// The item looking the most like the color, and how different it looks.
+    pub fn nearest(&self, color: ColorFormat, metric: DeltaE) -> Option<(usize, f32)> {
+        self.items
+            .iter()
+            .map(|x| metric.between(color, x.color))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Adds the color to the library, or renames it when it is already there. Saving without a
+    /// name keeps the existing one. Returns the index.
+    pub fn save_color(&mut self, color: ColorFormat, name: Option<String>) -> usize {
+        match self.position(color) {
+            Some(index) => {
+                if name.is_some() {
+                    self.items[index].name = name;
+                }
+                index
+            }
+            None => {
+                self.items.push(ColorItem::new(color.to_rgb(), name));
+                self.items.len() - 1
+            }
+        }
+    }
+
+    /// Adds imported items, skipping colors already in the library. Returns the number added.
+    pub fn import(&mut self, items: Vec<ColorItem>) -> usize {
+        let mut added = 0;
+        for item in items {
+            match self.position(item.color) {
+                Some(index) => {
+                    let existing = &mut self.items[index];
+                    if existing.name.is_none() {
+                        existing.name = item.name;
+                    }
+                    if existing.shades.is_empty() {
+                        existing.shades = item.shades;
+                    }
+                }
+                None => {
+                    self.items.push(item);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    pub fn rename(&mut self, index: usize, name: Option<String>) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.name = name;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<ColorItem> {
+        (index < self.items.len()).then(|| self.items.remove(index))
+    }
+
+    /// Puts back an item returned by `remove`.
+    pub fn restore(&mut self, index: usize, item: ColorItem) {
+        self.items.insert(index.min(self.items.len()), item);
+    }
+
+    /// `<config dir>/uicolors/library.json`, e.g. `~/Library/Application Support` on macOS.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("uicolors").join("library.json"))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let library: Self = serde_json::from_str(json)?;
+        library.migrate()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn migrate(mut self) -> Result<Self> {
+        if self.version > Self::VERSION {
+            return Err(NewerVersion(self.version).into());
+        }
+        // version 0 had the same layout, only without the version field
+        if self.version == 0 {
+            self.version = 1;
+        }
+        Ok(self)
+    }
+
+    /// Loads the library, or returns `None` when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+        };
+        let library =
+            Self::from_json(&json).with_context(|| format!("parsing {}", path.display()))?;
+        Ok(Some(library))
+    }
+
+    /// Renames an unreadable library to e.g. `library.json.1713400000.bak`, never replacing an
+    /// earlier backup. Returns the backup path.
+    pub fn move_aside(path: &Path) -> Result<PathBuf> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+        let backup = (0..)
+            .map(|i| {
+                let mut name = path.as_os_str().to_owned();
+                match i {
+                    0 => name.push(format!(".{}.bak", time)),
+                    i => name.push(format!(".{}-{}.bak", time, i)),
+                }
+                PathBuf::from(name)
+            })
+            .find(|x| !x.exists())
+            .unwrap();
+        fs::rename(path, &backup).with_context(|| format!("moving {} aside", path.display()))?;
+        Ok(backup)
+    }
+
+    /// Writes the library to a temporary file next to `path` then renames it over `path`, so a
+    /// crash never leaves a half written library behind.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = self.to_json()?;
+        write_atomic(path, json.as_bytes())
+    }
+}
+
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow!("invalid path {}", path.display()))?;
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = dir.join(tmp_name);
+    let mut file =
+        fs::File::create(&tmp_path).with_context(|| format!("writing {}", tmp_path.display()))?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("uicolors-test-{}", std::process::id()));
+        let path = dir.join("library.json");
+        assert!(ColorLibrary::load(&path).unwrap().is_none());
+
+        let mut library = ColorLibrary::default();
+        library.items[0].favorite = true;
+        library.copy_format = CopyFormat::Oklch;
+        library.save(&path).unwrap();
+        let loaded = ColorLibrary::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.version, ColorLibrary::VERSION);
+        assert_eq!(loaded.items.len(), library.items.len());
+        assert!(loaded.items[0].favorite);
+        assert_eq!(loaded.items[0].color, library.items[0].color);
+        assert_eq!(loaded.copy_format, CopyFormat::Oklch);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn edit() {
+        let mut library = ColorLibrary::default();
+        let n = library.items.len();
+        let color = ColorFormat::Hsl(200., 50., 40.);
+        let index = library.save_color(color, Some("Ocean".to_owned()));
+        assert_eq!((index, library.items.len()), (n, n + 1));
+        assert_eq!(library.position(color.to_rgb()), Some(n));
+        // a color that can't be told apart is the same item, a visibly different one isn't
+        let (index, distance) = library
+            .nearest(ColorFormat::Rgb(0x35, 0x79, 0x9a), DeltaE::Ciede2000)
+            .unwrap();
+        assert_eq!(index, n);
+        assert!(distance > 0. && distance < 1.);
+        assert_eq!(
+            library.position(ColorFormat::Rgb(0x35, 0x79, 0x9a)),
+            Some(n)
+        );
+        assert_eq!(library.position(ColorFormat::Rgb(0x40, 0x79, 0x9a)), None);
+
+        // saving the same color again renames it instead of adding a duplicate
+        let index = library.save_color(color.to_rgb(), Some("Sea".to_owned()));
+        assert_eq!((index, library.items.len()), (n, n + 1));
+        assert_eq!(library.items[n].name.as_deref(), Some("Sea"));
+        library.save_color(color, None);
+        assert_eq!(library.items[n].name.as_deref(), Some("Sea"));
+
+        let item = library.remove(0).unwrap();
+        assert_eq!(item.name.as_deref(), Some("Rose"));
+        assert!(library.remove(n).is_none());
+        library.restore(0, item);
+        assert_eq!(library.items[0].name.as_deref(), Some("Rose"));
+        assert_eq!(library.items.len(), n + 1);
+    }
+
+    #[test]
+    fn migrate() {
+        let v0 = r##"{ "items": [{ "color": "#f43f5e", "name": "Rose", "favorite": false }] }"##;
+        let library = ColorLibrary::from_json(v0).unwrap();
+        assert_eq!(library.version, ColorLibrary::VERSION);
+        assert_eq!(library.items[0].color, ColorFormat::Rgb(0xf4, 0x3f, 0x5e));
+
+        let future = r#"{ "version": 999, "items": [] }"#;
+        let err = ColorLibrary::from_json(future).err().unwrap();
+        assert!(matches!(err.downcast_ref(), Some(NewerVersion(999))));
+    }
+
+    #[test]
+    fn move_aside() {
+        let dir = std::env::temp_dir().join(format!("uicolors-aside-{}", std::process::id()));
+        let path = dir.join("library.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "first").unwrap();
+        let first = ColorLibrary::move_aside(&path).unwrap();
+        fs::write(&path, "second").unwrap();
+        let second = ColorLibrary::move_aside(&path).unwrap();
+        assert_ne!(first, second);
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(second).unwrap(), "second");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,26 +1,63 @@
 mod assets;
 mod cli;
+mod colorspace;
+mod contrast;
+mod css;
+mod cvd;
+mod design_system;
+mod difference;
+mod export;
+mod gamut;
+mod gradient;
+mod harmony;
 mod helpers;
+mod history;
+mod image_colors;
+mod import;
+mod library;
 mod models;
+mod random;
 mod states;
+mod tailwind;
 mod views;
 
 use assets::*;
 use clap::Parser;
+use contrast::*;
+use css::*;
+use cvd::*;
+use design_system::*;
+use difference::*;
+use export::*;
+use gamut::*;
 use gpui::*;
+use gradient::*;
+use harmony::*;
 use helpers::*;
+use history::*;
+use image_colors::*;
+use import::*;
+use library::*;
 use models::*;
+use random::*;
 use states::*;
+use tailwind::*;
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 use views::*;
 
-actions!(app, [Quit, RandomColor]);
+actions!(app, [Quit, RandomColor, Undo, Redo]);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = cli::Args::parse();
+    if let Some(command) = args.command {
+        // headless: print and exit without starting the app
+        cli::run(command)?;
+        return Ok(());
+    }
     init_log();
+    let initial_color = args.color;
 
     let app = App::new();
     let text_sys = app.text_system();
@@ -41,7 +78,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
 
         cx.open_window(opts, |cx| {
-            let workspace = cx.new_view(|cx| Workspace::new(cx));
+            let workspace = cx.new_view(|cx| Workspace::new(cx, initial_color));
             cx.focus_view(&workspace);
             workspace
         });
@@ -50,13 +87,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cx.on_action(|act: &Quit, cx| cx.quit());
         cx.bind_keys([
             KeyBinding::new("cmd-q", Quit, None),
-            KeyBinding::new("space", RandomColor, None),
+            // text fields keep their own shortcuts
+            KeyBinding::new("cmd-z", Undo, Some("Workspace && !TextField")),
+            KeyBinding::new("cmd-shift-z", Redo, Some("Workspace && !TextField")),
+            KeyBinding::new("space", RandomColor, Some("Workspace && !TextField")),
         ]);
 
         cx.set_menus(vec![Menu {
             name: "",
             items: vec![
                 MenuItem::action("Random color", RandomColor),
+                MenuItem::action("Undo", Undo),
+                MenuItem::action("Redo", Redo),
                 MenuItem::action("Quit", Quit),
             ],
         }]);
@@ -1,10 +1,19 @@
+use crate::{colorspace::*, parse_css_color, ParseColorError};
 use gpui::{hsla, Hsla, Rgba};
+use material_colors::Argb;
 use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ColorFormat {
-    Rgb(u8, u8, u8),  // 0..255, 0..255, 0..255
-    Hsl(u16, u8, u8), // 0..360, 0..100, 0..100
+    Rgb(u8, u8, u8),           // 0..255, 0..255, 0..255
+    Rgba(u8, u8, u8, u8),      // 0..255, 0..255, 0..255, 0..255
+    Hsl(f32, f32, f32),        // 0..360, 0..100, 0..100
+    Hsla(f32, f32, f32, f32),  // 0..360, 0..100, 0..100, 0..100
+    Oklab(f32, f32, f32, f32), // 0..1, -0.4..0.4, -0.4..0.4, alpha 0..1
+    Oklch(f32, f32, f32, f32), // 0..1, 0..0.4, 0..360, alpha 0..1
+    Lab(f32, f32, f32, f32),   // 0..100, -125..125, -125..125, alpha 0..1
+    Lch(f32, f32, f32, f32),   // 0..100, 0..150, 0..360, alpha 0..1
+    Hct(f32, f32, f32, f32),   // hue 0..360, chroma 0..~150, tone 0..100, alpha 0..1
 }
 
 impl ColorFormat {
@@ -16,97 +25,364 @@ impl ColorFormat {
     }
 
     pub fn random_hsl() -> ColorFormat {
-        let h = (rand::random::<usize>() % 360) as u16;
-        let s = rand::random::<u8>() % 100;
-        let l = rand::random::<u8>() % 100;
+        let h = (rand::random::<usize>() % 360) as f32;
+        let s = (rand::random::<u8>() % 100) as f32;
+        let l = (rand::random::<u8>() % 100) as f32;
         ColorFormat::Hsl(h, s, l)
     }
 
     pub fn to_string(&self) -> String {
-        match self {
+        // perceptual colors are written in CSS syntax, with as few digits as needed
+        fn num(x: f32, precision: usize) -> String {
+            let s = format!("{:.*}", precision, x);
+            let s = s.trim_end_matches('0').trim_end_matches('.');
+            if s == "-0" {
+                "0".to_owned()
+            } else {
+                s.to_owned()
+            }
+        }
+        // the shortest text that parses back to the same f32
+        fn exact(x: f32) -> String {
+            if x == 0. {
+                "0".to_owned()
+            } else {
+                x.to_string()
+            }
+        }
+        fn call(name: &str, c: [String; 3], a: f32) -> String {
+            let alpha = if a < 1. {
+                format!(" / {}", num(a, 3))
+            } else {
+                "".to_owned()
+            };
+            let [c0, c1, c2] = c;
+            format!("{}({} {} {}{})", name, c0, c1, c2, alpha)
+        }
+        fn func(name: &str, c: [f32; 3], precision: [usize; 3], a: f32) -> String {
+            let [p0, p1, p2] = precision;
+            call(name, [num(c[0], p0), num(c[1], p1), num(c[2], p2)], a)
+        }
+
+        match *self {
             ColorFormat::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
-            ColorFormat::Hsl(h, s, l) => format!("{},{},{}", h, s, l),
+            ColorFormat::Rgba(r, g, b, a) => format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+            // two decimals are enough for any 24-bit color to survive a round trip
+            ColorFormat::Hsl(h, s, l) => format!("{},{},{}", num(h, 2), num(s, 2), num(l, 2)),
+            ColorFormat::Hsla(h, s, l, a) => {
+                let [h, s, l, a] = [h, s, l, a].map(|x| num(x, 2));
+                format!("{},{},{},{}", h, s, l, a)
+            }
+            ColorFormat::Oklab(l, a, b, alpha) => func("oklab", [l, a, b], [5, 5, 5], alpha),
+            ColorFormat::Oklch(l, c, h, alpha) => func("oklch", [l, c, h], [5, 5, 3], alpha),
+            ColorFormat::Lab(l, a, b, alpha) => func("lab", [l, a, b], [3, 3, 3], alpha),
+            ColorFormat::Lch(l, c, h, alpha) => func("lch", [l, c, h], [3, 3, 3], alpha),
+            // the HCT solver turns the smallest rounding near the gamut edge into another color
+            ColorFormat::Hct(h, c, t, alpha) => call("hct", [h, c, t].map(exact), alpha),
         }
     }
 
-    pub fn parse(s: &str) -> Option<ColorFormat> {
-        if s.starts_with("#") {
-            // parse from hex
-            let hex = s.trim_start_matches("#");
-            let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-            let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-            let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-            return Some(ColorFormat::Rgb(r, g, b).normalize());
-        } else {
-            // parse from hsl
-            let parts: Vec<&str> = s.split(",").collect();
-            let h: u16 = parts[0].parse().ok()?;
-            let s: u8 = parts[1].parse().ok()?;
-            let l: u8 = parts[2].parse().ok()?;
-            return Some(ColorFormat::Hsl(h, s, l).normalize());
-        }
+    /// Parses a CSS color such as `#f43f5e`, `rgb(244 63 94 / 50%)`, `oklch(0.65 0.2 15)`
+    /// or `rebeccapurple`, as well as the `h,s,l` form produced by `to_string`.
+    pub fn parse(s: &str) -> Result<ColorFormat, ParseColorError> {
+        parse_css_color(s)
+    }
+
+    /// Builds an RGB color from gamma-encoded sRGB channels in 0..1, clipping out of range values.
+    pub fn from_srgb(rgb: Vec3, alpha: f32) -> ColorFormat {
+        let [r, g, b] = rgb.map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+        let a = (alpha.clamp(0., 1.) * 255.).round() as u8;
+        ColorFormat::Rgba(r, g, b, a).opaque_if_possible()
+    }
+
+    pub fn from_linear_srgb(rgb: Vec3, alpha: f32) -> ColorFormat {
+        Self::from_srgb(linear_srgb_to_srgb(rgb), alpha)
     }
 
     pub fn normalize(self) -> ColorFormat {
+        fn huef(h: f32) -> f32 {
+            h.rem_euclid(360.)
+        }
+
         match self {
             ColorFormat::Rgb(r, g, b) => ColorFormat::Rgb(r.min(255), g.min(255), b.min(255)),
+            ColorFormat::Rgba(r, g, b, a) => ColorFormat::Rgba(r, g, b, a),
             ColorFormat::Hsl(h, s, l) => {
-                let h = if h < 0 || h > 360 { h % 360 } else { h };
-                ColorFormat::Hsl(h, s.min(100), l.min(100))
+                ColorFormat::Hsl(huef(h), s.clamp(0., 100.), l.clamp(0., 100.))
+            }
+            ColorFormat::Hsla(h, s, l, a) => ColorFormat::Hsla(
+                huef(h),
+                s.clamp(0., 100.),
+                l.clamp(0., 100.),
+                a.clamp(0., 100.),
+            ),
+            ColorFormat::Oklab(l, a, b, alpha) => {
+                ColorFormat::Oklab(l.clamp(0., 1.), a, b, alpha.clamp(0., 1.))
+            }
+            ColorFormat::Oklch(l, c, h, alpha) => {
+                ColorFormat::Oklch(l.clamp(0., 1.), c.max(0.), huef(h), alpha.clamp(0., 1.))
+            }
+            ColorFormat::Lab(l, a, b, alpha) => {
+                ColorFormat::Lab(l.clamp(0., 100.), a, b, alpha.clamp(0., 1.))
+            }
+            ColorFormat::Lch(l, c, h, alpha) => {
+                ColorFormat::Lch(l.clamp(0., 100.), c.max(0.), huef(h), alpha.clamp(0., 1.))
+            }
+            ColorFormat::Hct(h, c, t, alpha) => {
+                ColorFormat::Hct(huef(h), c.max(0.), t.clamp(0., 100.), alpha.clamp(0., 1.))
             }
         }
     }
 
     pub fn canonicalize(&self) -> Hsla {
         match self.normalize() {
-            ColorFormat::Rgb(r, g, b) => {
+            ColorFormat::Rgb(r, g, b) => ColorFormat::Rgba(r, g, b, 255).canonicalize(),
+            ColorFormat::Rgba(r, g, b, a) => {
                 let r = r as f32 / 255.;
                 let g = g as f32 / 255.;
                 let b = b as f32 / 255.;
-                Rgba { r, g, b, a: 1. }.into()
+                let a = a as f32 / 255.;
+                Rgba { r, g, b, a }.into()
             }
-            ColorFormat::Hsl(h, s, l) => {
-                let h = h as f32 / 360.;
-                let s = s as f32 / 100.;
-                let l = l as f32 / 100.;
-                Hsla { h, s, l, a: 1. }
+            ColorFormat::Hsl(h, s, l) => ColorFormat::Hsla(h, s, l, 100.).canonicalize(),
+            ColorFormat::Hsla(h, s, l, a) => Hsla {
+                h: h / 360.,
+                s: s / 100.,
+                l: l / 100.,
+                a: a / 100.,
+            },
+            color => {
+                let [r, g, b] = color.to_srgb().map(|c| c.clamp(0., 1.));
+                Rgba {
+                    r,
+                    g,
+                    b,
+                    a: color.alpha(),
+                }
+                .into()
             }
         }
     }
 
-    pub fn to_rgb(self) -> ColorFormat {
+    /// Gamma-encoded sRGB channels. Perceptual colors outside the sRGB gamut give values
+    /// outside 0..1, which are left for the caller to clip or map.
+    pub fn to_srgb(self) -> Vec3 {
         match self.normalize() {
-            ColorFormat::Rgb(_, _, _) => self,
-            ColorFormat::Hsl(h, s, l) => {
-                let Rgba { r, g, b, a: _ } = self.canonicalize().into();
-                ColorFormat::Rgb((r * 255.) as u8, (g * 255.) as u8, (b * 255.) as u8)
+            ColorFormat::Rgb(r, g, b) | ColorFormat::Rgba(r, g, b, _) => {
+                [r, g, b].map(|c| c as f32 / 255.)
+            }
+            ColorFormat::Hsl(h, s, l) | ColorFormat::Hsla(h, s, l, _) => {
+                hsl_to_srgb([h, s / 100., l / 100.])
+            }
+            ColorFormat::Hct(h, c, t, _) => {
+                let argb = Argb::from(material_colors::Hct::from(h as f64, c as f64, t as f64));
+                [argb.red, argb.green, argb.blue].map(|c| c as f32 / 255.)
             }
+            color => linear_srgb_to_srgb(color.to_linear_srgb()),
+        }
+    }
+
+    pub fn to_linear_srgb(self) -> Vec3 {
+        match self.normalize() {
+            ColorFormat::Oklab(l, a, b, _) => oklab_to_linear_srgb([l, a, b]),
+            ColorFormat::Oklch(l, c, h, _) => oklab_to_linear_srgb(lch_to_lab([l, c, h])),
+            ColorFormat::Lab(l, a, b, _) => lab_to_linear_srgb([l, a, b]),
+            ColorFormat::Lch(l, c, h, _) => lab_to_linear_srgb(lch_to_lab([l, c, h])),
+            color => srgb_to_linear_srgb(color.to_srgb()),
+        }
+    }
+
+    /// Alpha channel in 0..1, where 1 is fully opaque.
+    pub fn alpha(self) -> f32 {
+        match self.normalize() {
+            ColorFormat::Rgb(..) | ColorFormat::Hsl(..) => 1.,
+            ColorFormat::Rgba(_, _, _, a) => a as f32 / 255.,
+            ColorFormat::Hsla(_, _, _, a) => a / 100.,
+            ColorFormat::Oklab(_, _, _, a)
+            | ColorFormat::Oklch(_, _, _, a)
+            | ColorFormat::Lab(_, _, _, a)
+            | ColorFormat::Lch(_, _, _, a)
+            | ColorFormat::Hct(_, _, _, a) => a,
+        }
+    }
+
+    pub fn is_opaque(self) -> bool {
+        self.alpha() >= 1.
+    }
+
+    /// Returns the same color with the given alpha (0..1), keeping its color space.
+    pub fn with_alpha(self, alpha: f32) -> ColorFormat {
+        let alpha = alpha.clamp(0., 1.);
+        match self.normalize() {
+            ColorFormat::Rgb(r, g, b) | ColorFormat::Rgba(r, g, b, _) => {
+                ColorFormat::Rgba(r, g, b, (alpha * 255.).round() as u8).opaque_if_possible()
+            }
+            ColorFormat::Hsl(h, s, l) | ColorFormat::Hsla(h, s, l, _) => {
+                ColorFormat::Hsla(h, s, l, alpha * 100.).opaque_if_possible()
+            }
+            ColorFormat::Oklab(l, a, b, _) => ColorFormat::Oklab(l, a, b, alpha),
+            ColorFormat::Oklch(l, c, h, _) => ColorFormat::Oklch(l, c, h, alpha),
+            ColorFormat::Lab(l, a, b, _) => ColorFormat::Lab(l, a, b, alpha),
+            ColorFormat::Lch(l, c, h, _) => ColorFormat::Lch(l, c, h, alpha),
+            ColorFormat::Hct(h, c, t, _) => ColorFormat::Hct(h, c, t, alpha),
+        }
+    }
+
+    // collapse fully opaque RGBA/HSLA back to RGB/HSL, so that they compare equal
+    pub fn opaque_if_possible(self) -> ColorFormat {
+        match self {
+            ColorFormat::Rgba(r, g, b, 255) => ColorFormat::Rgb(r, g, b),
+            ColorFormat::Hsla(h, s, l, a) if a >= 100. => ColorFormat::Hsl(h, s, l),
+            _ => self,
+        }
+    }
+
+    pub fn is_perceptual(self) -> bool {
+        !matches!(
+            self,
+            ColorFormat::Rgb(..)
+                | ColorFormat::Rgba(..)
+                | ColorFormat::Hsl(..)
+                | ColorFormat::Hsla(..)
+        )
+    }
+
+    pub fn to_rgb(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Rgb(_, _, _) | ColorFormat::Rgba(_, _, _, _) => self,
+            color => ColorFormat::from_srgb(color.to_srgb(), color.alpha()),
         }
     }
 
     pub fn to_hsl(self) -> ColorFormat {
         match self.normalize() {
-            ColorFormat::Hsl(_, _, _) => self,
-            ColorFormat::Rgb(_, _, _) => {
-                let Hsla { h, l, s, a: _ } = self.canonicalize();
-                ColorFormat::Hsl((h * 360.) as u16, (s * 100.) as u8, (l * 100.) as u8)
+            ColorFormat::Hsl(_, _, _) | ColorFormat::Hsla(_, _, _, _) => self,
+            // out of gamut colors are clipped, as HSL can't express them
+            color => {
+                let [h, s, l] = srgb_to_hsl(color.to_srgb().map(|c| c.clamp(0., 1.)));
+                ColorFormat::Hsla(h, s * 100., l * 100., color.alpha() * 100.).opaque_if_possible()
+            }
+        }
+    }
+
+    pub fn to_oklab(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Oklab(..) => self,
+            ColorFormat::Oklch(l, c, h, alpha) => {
+                let [l, a, b] = lch_to_lab([l, c, h]);
+                ColorFormat::Oklab(l, a, b, alpha)
+            }
+            color => {
+                let [l, a, b] = linear_srgb_to_oklab(color.to_linear_srgb());
+                ColorFormat::Oklab(l, a, b, color.alpha())
+            }
+        }
+    }
+
+    pub fn to_oklch(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Oklch(..) => self,
+            color => {
+                let (l, a, b) = color.split_oklab();
+                let [l, c, h] = lab_to_lch([l, a, b]);
+                ColorFormat::Oklch(l, c, h, color.alpha())
             }
         }
     }
 
-    pub fn split_hsl(self) -> (u16, u8, u8) {
+    pub fn to_lab(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Lab(..) => self,
+            ColorFormat::Lch(l, c, h, alpha) => {
+                let [l, a, b] = lch_to_lab([l, c, h]);
+                ColorFormat::Lab(l, a, b, alpha)
+            }
+            color => {
+                let [l, a, b] = linear_srgb_to_lab(color.to_linear_srgb());
+                ColorFormat::Lab(l, a, b, color.alpha())
+            }
+        }
+    }
+
+    pub fn to_lch(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Lch(..) => self,
+            color => {
+                let (l, a, b) = color.split_lab();
+                let [l, c, h] = lab_to_lch([l, a, b]);
+                ColorFormat::Lch(l, c, h, color.alpha())
+            }
+        }
+    }
+
+    pub fn to_hct(self) -> ColorFormat {
+        match self.normalize() {
+            ColorFormat::Hct(..) => self,
+            color => {
+                let (r, g, b) = color.split_rgb();
+                let hct = material_colors::Hct::new(Argb::new(255, r, g, b));
+                let (h, c, t) = (hct.get_hue(), hct.get_chroma(), hct.get_tone());
+                ColorFormat::Hct(h as f32, c as f32, t as f32, color.alpha())
+            }
+        }
+    }
+
+    pub fn split_hsl(self) -> (f32, f32, f32) {
         match self {
-            ColorFormat::Hsl(h, s, l) => (h, s, l),
-            ColorFormat::Rgb(_, _, _) => self.to_hsl().split_hsl(),
+            ColorFormat::Hsl(h, s, l) | ColorFormat::Hsla(h, s, l, _) => (h, s, l),
+            _ => self.to_hsl().split_hsl(),
         }
     }
 
     pub fn split_rgb(self) -> (u8, u8, u8) {
         match self {
-            ColorFormat::Rgb(r, g, b) => (r, g, b),
-            ColorFormat::Hsl(_, _, _) => self.to_rgb().split_rgb(),
+            ColorFormat::Rgb(r, g, b) | ColorFormat::Rgba(r, g, b, _) => (r, g, b),
+            _ => self.to_rgb().split_rgb(),
         }
     }
+
+    pub fn split_oklab(self) -> (f32, f32, f32) {
+        match self.to_oklab() {
+            ColorFormat::Oklab(l, a, b, _) => (l, a, b),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn split_oklch(self) -> (f32, f32, f32) {
+        match self.to_oklch() {
+            ColorFormat::Oklch(l, c, h, _) => (l, c, h),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn split_lab(self) -> (f32, f32, f32) {
+        match self.to_lab() {
+            ColorFormat::Lab(l, a, b, _) => (l, a, b),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn split_lch(self) -> (f32, f32, f32) {
+        match self.to_lch() {
+            ColorFormat::Lch(l, c, h, _) => (l, c, h),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn split_hct(self) -> (f32, f32, f32) {
+        match self.to_hct() {
+            ColorFormat::Hct(h, c, t, _) => (h, c, t),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl std::str::FromStr for ColorFormat {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ColorFormat::parse(s)
+    }
 }
 
 impl From<&str> for ColorFormat {
@@ -117,19 +393,15 @@ impl From<&str> for ColorFormat {
 
 impl From<Hsla> for ColorFormat {
     fn from(hsla: Hsla) -> Self {
-        let h = (hsla.h * 360.) as u16;
-        let s = (hsla.s * 100.) as u8;
-        let l = (hsla.l * 100.) as u8;
-        ColorFormat::Hsl(h, s, l)
+        let a = hsla.a.clamp(0., 1.) * 100.;
+        ColorFormat::Hsla(hsla.h * 360., hsla.s * 100., hsla.l * 100., a).opaque_if_possible()
     }
 }
 
+// channels outside 0..1 are clipped rather than wrapped; see `Gamut::map` for better mappings
 impl From<Rgba> for ColorFormat {
     fn from(rgba: Rgba) -> Self {
-        let r = (rgba.r * 255.) as u8;
-        let g = (rgba.g * 255.) as u8;
-        let b = (rgba.b * 255.) as u8;
-        ColorFormat::Rgb(r, g, b)
+        ColorFormat::from_srgb([rgba.r, rgba.g, rgba.b], rgba.a)
     }
 }
 
@@ -167,50 +439,210 @@ impl<'de> Deserialize<'de> for ColorFormat {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        ColorFormat::parse(&s).ok_or_else(|| serde::de::Error::custom("invalid color format"))
+        ColorFormat::parse(&s).map_err(serde::de::Error::custom)
     }
 }
 
+pub static SCALES: [usize; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
 // convert 0 50 100 .. 900 950 1000 to 1..0
 pub fn darkness(l: usize) -> f32 {
     (1000 - l.min(1000)) as f32 / 1000.
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaletteMode {
+    Hsl,   // even steps in HSL lightness
+    Oklch, // perceptual steps in OKLCH, gamut mapped back into sRGB
+}
+
+/// Easing curve from (0, 0) to (1, 1), defined like CSS `cubic-bezier(x1, y1, x2, y2)`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CubicBezier(pub f32, pub f32, pub f32, pub f32);
+
+impl CubicBezier {
+    pub const LINEAR: Self = Self(0., 0., 1., 1.);
+    pub const EASE_IN: Self = Self(0.42, 0., 1., 1.);
+    pub const EASE_OUT: Self = Self(0., 0., 0.58, 1.);
+    pub const EASE_IN_OUT: Self = Self(0.42, 0., 0.58, 1.);
+
+    pub const PRESETS: [(&'static str, CubicBezier); 4] = [
+        ("linear", Self::LINEAR),
+        ("ease-in", Self::EASE_IN),
+        ("ease-out", Self::EASE_OUT),
+        ("ease-in-out", Self::EASE_IN_OUT),
+    ];
+
+    pub fn eval(self, x: f32) -> f32 {
+        let Self(x1, y1, x2, y2) = self;
+        let bezier = |t: f32, p1: f32, p2: f32| {
+            let u = 1. - t;
+            3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+        };
+
+        // x(t) is monotonic when x1 and x2 are in 0..1, so bisection finds t for x
+        let x = x.clamp(0., 1.);
+        let (mut lo, mut hi, mut t) = (0., 1., x);
+        for _ in 0..32 {
+            let bx = bezier(t, x1, x2);
+            if (bx - x).abs() < 1e-5 {
+                break;
+            }
+            if bx < x {
+                lo = t;
+            } else {
+                hi = t;
+            }
+            t = (lo + hi) / 2.;
+        }
+        bezier(t, y1, y2)
+    }
+
+    pub fn name(self) -> String {
+        match Self::PRESETS.iter().find(|(_, x)| *x == self) {
+            Some((name, _)) => name.to_string(),
+            None => format!(
+                "cubic-bezier({}, {}, {}, {})",
+                self.0, self.1, self.2, self.3
+            ),
+        }
+    }
+
+    /// The preset after this one, for cycling through them in the UI.
+    pub fn next_preset(self) -> Self {
+        let i = Self::PRESETS.iter().position(|(_, x)| *x == self);
+        let i = i.map(|i| (i + 1) % Self::PRESETS.len()).unwrap_or(0);
+        Self::PRESETS[i].1
+    }
+
+    /// Parses a preset name or `cubic-bezier(x1, y1, x2, y2)` as written by `name`, with or
+    /// without the `cubic-bezier()`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let preset = Self::PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s));
+        if let Some((_, curve)) = preset {
+            return Ok(*curve);
+        }
+        let args = s
+            .strip_prefix("cubic-bezier(")
+            .and_then(|x| x.strip_suffix(')'))
+            .unwrap_or(s);
+        let values = args
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|x| !x.is_empty())
+            .map(|x| x.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("\"{}\" is not an easing curve", s))?;
+        let [x1, y1, x2, y2] = values[..] else {
+            return Err("an easing curve has four numbers".to_owned());
+        };
+        if !(0. ..=1.).contains(&x1) || !(0. ..=1.).contains(&x2) {
+            return Err("x1 and x2 must be between 0 and 1".to_owned());
+        }
+        Ok(Self(x1, y1, x2, y2))
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct ColorPalette {
     pub base: Hsla,
     pub hue_shift: f32,
+    pub mode: PaletteMode,
+    // OKLCH mode only: lightness of the 50 and 950 shades, and the easing between them
+    pub lightness_range: (f32, f32),
+    pub lightness_curve: CubicBezier,
+    // OKLCH mode only: how chroma rises from the ends of the scale to the base color at 500
+    pub chroma_curve: CubicBezier,
 }
 
 impl ColorPalette {
     pub fn new(h: f32, s: f32) -> Self {
-        Self {
-            base: hsla(h, s, 0.5, 1.),
-            hue_shift: 0.,
-        }
+        Self::from(hsla(h, s, 0.5, 1.))
     }
 
     pub fn from(color: Hsla) -> Self {
         Self {
             base: color,
             hue_shift: 0.,
+            mode: PaletteMode::Hsl,
+            lightness_range: (0.97, 0.26),
+            lightness_curve: CubicBezier::LINEAR,
+            chroma_curve: CubicBezier::EASE_OUT,
         }
     }
 
+    /// Keeps the generation settings and replaces the base color.
+    pub fn with_base(mut self, base: Hsla) -> Self {
+        self.base = base;
+        self
+    }
+
     pub fn hue_shift(mut self, hue_shift: f32) -> Self {
         self.hue_shift = hue_shift;
         self
     }
 
+    pub fn mode(mut self, mode: PaletteMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn lightness_range(mut self, lightest: f32, darkest: f32) -> Self {
+        self.lightness_range = (lightest, darkest);
+        self
+    }
+
+    pub fn lightness_curve(mut self, curve: CubicBezier) -> Self {
+        self.lightness_curve = curve;
+        self
+    }
+
+    pub fn chroma_curve(mut self, curve: CubicBezier) -> Self {
+        self.chroma_curve = curve;
+        self
+    }
+
     pub fn at(self, l: f32) -> Hsla {
+        match self.mode {
+            PaletteMode::Hsl => self.at_hsl(l),
+            PaletteMode::Oklch => self.at_oklch(l),
+        }
+    }
+
+    fn at_hsl(self, l: f32) -> Hsla {
         let Self {
             base: Hsla { h, s, .. },
             hue_shift,
+            ..
         } = self;
         let l = l.clamp(0., 1.);
         let h = (h + (l - 0.5) * hue_shift).fract();
         let h = if h < 0. { h + 1. } else { h };
-        Hsla { h, s, l, a: 1. }
+        Hsla {
+            h,
+            s,
+            l,
+            a: self.base.a,
+        }
+    }
+
+    fn at_oklch(self, l: f32) -> Hsla {
+        let Rgba { r, g, b, a } = self.base.into();
+        let [_, base_c, base_h] = lab_to_lch(linear_srgb_to_oklab(srgb_to_linear_srgb([r, g, b])));
+
+        // t goes from 0 at the lightest shade (50) to 1 at the darkest (950)
+        let l = l.clamp(0., 1.);
+        let t = ((1. - l - 0.05) / 0.9).clamp(0., 1.);
+        let (lightest, darkest) = self.lightness_range;
+        let ok_l = lightest + (darkest - lightest) * self.lightness_curve.eval(t);
+        let ok_c = base_c * self.chroma_curve.eval(1. - (2. * (1. - l) - 1.).abs());
+        let ok_h = (base_h + (l - 0.5) * self.hue_shift * 360.).rem_euclid(360.);
+
+        let [r, g, b] = oklch_to_srgb_gamut_mapped([ok_l, ok_c, ok_h]);
+        Rgba { r, g, b, a }.into()
     }
 
     pub fn at_darkness(self, l: usize) -> Hsla {
@@ -234,85 +666,173 @@ impl ColorPalette {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ColorItem {
-    pub color: ColorFormat,
-    pub name: Option<String>,
-    pub favorite: bool,
-}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ColorLibrary;
 
-impl ColorItem {
-    pub fn new(color: ColorFormat, name: Option<String>) -> Self {
-        Self {
-            color,
-            name: name.into(),
-            favorite: false,
-        }
+    #[test]
+    fn parse() {
+        assert_eq!(
+            ColorFormat::parse("#ee00ff"),
+            Ok(ColorFormat::Rgb(238, 0, 255))
+        );
+    }
+    #[test]
+    fn canonicalize() {
+        let c0 = ColorFormat::Hsl(400., 120., 120.);
+        let c1 = c0.canonicalize();
+        assert_eq!(c0, ColorFormat::Hsl(400., 120., 120.));
+        assert_eq!(c1, ColorFormat::Hsl(40., 100., 100.).canonicalize());
     }
-}
 
-#[derive(Serialize, Deserialize)]
-pub struct ColorLibrary {
-    pub items: Vec<ColorItem>,
-}
+    #[test]
+    fn parse_alpha() {
+        assert_eq!(
+            ColorFormat::parse("#ee00ff80"),
+            Ok(ColorFormat::Rgba(238, 0, 255, 128))
+        );
+        assert_eq!(
+            ColorFormat::parse("hsla(120, 50%, 40%, 0.25)"),
+            Ok(ColorFormat::Hsla(120., 50., 40., 25.))
+        );
+        assert_eq!(
+            ColorFormat::parse("#ee00ffff"),
+            Ok(ColorFormat::Rgb(238, 0, 255))
+        );
+    }
 
-impl Default for ColorLibrary {
-    fn default() -> Self {
-        fn item(hex: &str, name: &str) -> ColorItem {
-            ColorItem::new(hex.into(), Some(name.to_owned()))
-        }
+    #[test]
+    fn keep_alpha() {
+        let c = ColorFormat::Rgba(238, 0, 255, 128);
+        assert!((c.to_hsl().alpha() - c.alpha()).abs() < 1e-6);
+        assert_eq!(c.to_hsl().to_rgb(), c);
+        assert_eq!(ColorFormat::from(c.canonicalize()).to_rgb(), c);
+        assert_eq!(
+            ColorFormat::Hsla(120., 50., 40., 100.).to_rgb(),
+            ColorFormat::Hsl(120., 50., 40.).to_rgb()
+        );
+    }
 
-        Self {
-            items: vec![
-                item("#f43f5e", "Rose"),
-                item("#ec4899", "Pink"),
-                item("#d946ef", "Fuchsia"),
-                item("#a855f7", "Purple"),
-                item("#8b5cf6", "Violet"),
-                item("#6366f1", "Indigo"),
-                item("#3b82f6", "Blue"),
-                item("#0ea5e9", "Sky"),
-                item("#06b6d4", "Cyan"),
-                item("#14b8a6", "Teal"),
-                item("#10b981", "Emerald"),
-                item("#22c55e", "Green"),
-                item("#84cc16", "Lime"),
-                item("#eab308", "Yellow"),
-                item("#f59e0b", "Amber"),
-                item("#f97316", "Orange"),
-                item("#ef4444", "Red"),
-                item("#78716c", "Stone"),
-                item("#737373", "Neutral"),
-            ],
+    fn assert_close(a: (f32, f32, f32), b: (f32, f32, f32), eps: f32) {
+        let d = (a.0 - b.0)
+            .abs()
+            .max((a.1 - b.1).abs())
+            .max((a.2 - b.2).abs());
+        assert!(d <= eps, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn perceptual_reference_values() {
+        let red = ColorFormat::Rgb(255, 0, 0);
+        assert_close(red.split_oklab(), (0.62796, 0.22486, 0.12585), 1e-4);
+        assert_close(red.split_oklch(), (0.62796, 0.25768, 29.2339), 1e-3);
+        assert_close(red.split_lab(), (54.2905, 80.8049, 69.8910), 1e-2);
+        assert_close(red.split_lch(), (54.2905, 106.8372, 40.8526), 1e-2);
+        assert_close(red.split_hct(), (27.4, 113.4, 53.2), 0.1);
+    }
+
+    #[test]
+    fn perceptual_round_trip() {
+        let spaces: [fn(ColorFormat) -> ColorFormat; 5] = [
+            ColorFormat::to_oklab,
+            ColorFormat::to_oklch,
+            ColorFormat::to_lab,
+            ColorFormat::to_lch,
+            ColorFormat::to_hct,
+        ];
+        for item in ColorLibrary::default().items {
+            for to_space in spaces {
+                let color = to_space(item.color);
+                assert!(color.is_perceptual());
+                assert_eq!(color.to_rgb(), item.color, "{}", color);
+                assert_eq!(
+                    ColorFormat::parse(&color.to_string()).unwrap().to_rgb(),
+                    item.color
+                );
+            }
         }
+        let translucent = ColorFormat::Rgba(16, 185, 129, 64);
+        assert_eq!(translucent.to_oklch().to_rgb(), translucent);
+        assert_eq!(translucent.to_hct().alpha(), translucent.alpha());
     }
-}
 
-impl ColorLibrary {
-    pub fn position(&self, color: ColorFormat) -> Option<usize> {
-        let (rgb, hsl) = (color.to_rgb(), color.to_hsl());
-        self.items
-            .iter()
-            .position(|x| x.color == rgb || x.color == hsl)
+    // the 24-bit colors from `start` on, `step` apart, survive a trip to each space and back,
+    // and through the text form
+    fn assert_round_trips(start: u32, step: usize) {
+        let spaces: [fn(ColorFormat) -> ColorFormat; 6] = [
+            ColorFormat::to_hsl,
+            ColorFormat::to_oklab,
+            ColorFormat::to_oklch,
+            ColorFormat::to_lab,
+            ColorFormat::to_lch,
+            ColorFormat::to_hct,
+        ];
+        let threads = std::thread::available_parallelism().map_or(4, |x| x.get());
+        std::thread::scope(|scope| {
+            for thread in 0..threads {
+                scope.spawn(move || {
+                    let first = start + (thread * step) as u32;
+                    for rgb in (first..1 << 24).step_by(threads * step) {
+                        let [_, r, g, b] = rgb.to_be_bytes();
+                        let color = ColorFormat::Rgb(r, g, b);
+                        for to_space in spaces {
+                            let converted = to_space(color);
+                            assert_eq!(converted.to_rgb(), color, "{}", converted);
+                            let parsed = ColorFormat::parse(&converted.to_string()).unwrap();
+                            assert_eq!(parsed.to_rgb(), color, "{}", converted);
+                        }
+                    }
+                });
+            }
+        });
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    #[test]
+    fn lossless_round_trip() {
+        assert_round_trips(0, 251);
+    }
 
+    // takes minutes in debug builds: cargo test --release -- --ignored
     #[test]
-    fn parse() {
-        assert_eq!(
-            ColorFormat::parse("#ee00ff"),
-            Some(()).map(|_| ColorFormat::Rgb(238, 0, 255))
-        );
+    #[ignore]
+    fn lossless_round_trip_every_color() {
+        assert_round_trips(0, 1);
     }
+
     #[test]
-    fn canonicalize() {
-        let c0 = ColorFormat::Hsl(400, 120, 120);
-        let c1 = c0.canonicalize();
-        assert_eq!(c0, ColorFormat::Hsl(400, 120, 120));
-        assert_eq!(c1, ColorFormat::Hsl(40, 100, 100));
+    fn oklch_palette() {
+        let base = ColorFormat::from("#ef4444").canonicalize();
+        let palette = ColorPalette::from(base).mode(PaletteMode::Oklch);
+        let shades: Vec<Vec3> = (palette.colors().into_iter())
+            .map(|c| {
+                let Rgba { r, g, b, .. } = c.into();
+                lab_to_lch(linear_srgb_to_oklab(srgb_to_linear_srgb([r, g, b])))
+            })
+            .collect();
+        let lightness: Vec<f32> = shades.iter().map(|c| c[0]).collect();
+        assert!(lightness.windows(2).all(|w| w[0] > w[1]), "{:?}", lightness);
+        assert!((lightness[0] - 0.97).abs() < 0.01, "{:?}", lightness);
+        assert!((lightness[10] - 0.26).abs() < 0.01, "{:?}", lightness);
+        // mid shades are more colorful than the ends
+        assert!(shades[5][1] > shades[0][1] && shades[5][1] > shades[10][1]);
+    }
+
+    #[test]
+    fn cubic_bezier() {
+        assert!((CubicBezier::LINEAR.eval(0.3) - 0.3).abs() < 1e-3);
+        assert_eq!(CubicBezier::EASE_IN_OUT.eval(0.), 0.);
+        assert_eq!(CubicBezier::EASE_IN_OUT.eval(1.), 1.);
+        assert!((CubicBezier::EASE_IN_OUT.eval(0.5) - 0.5).abs() < 1e-3);
+        assert!(CubicBezier::EASE_IN.eval(0.5) < 0.5);
+        assert!(CubicBezier::EASE_OUT.eval(0.5) > 0.5);
+
+        let custom = CubicBezier(0.25, 0.1, 0.25, 1.);
+        assert_eq!(CubicBezier::parse(&custom.name()), Ok(custom));
+        assert_eq!(CubicBezier::parse("0.25 0.1 0.25 1"), Ok(custom));
+        assert_eq!(CubicBezier::parse("Ease-In"), Ok(CubicBezier::EASE_IN));
+        assert!(CubicBezier::parse("0.25 0.1 0.25").is_err());
+        assert!(CubicBezier::parse("1.5 0 0.5 1").is_err());
+        assert!(CubicBezier::parse("bouncy").is_err());
     }
 }
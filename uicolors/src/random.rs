@@ -0,0 +1,193 @@
+// Random colors within OKLCH ranges, so that random picks avoid the near-black, near-white and
+// grayish corners of the HSL cube.
+
+use crate::{colorspace::*, ColorFormat};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomConfig {
+    pub hue: (f32, f32), // degrees, wrapping around when the start is after the end
+    pub chroma: (f32, f32),
+    pub lightness: (f32, f32),
+    pub harmonious: bool, // hues related to the current color, instead of the hue range
+    pub seed: Option<u64>,
+}
+
+impl Default for RandomConfig {
+    fn default() -> Self {
+        Self::PRESETS[1].1
+    }
+}
+
+impl RandomConfig {
+    pub const PRESETS: [(&'static str, RandomConfig); 5] = [
+        ("Any", Self::preset((0., 0.37), (0., 1.))),
+        ("Vivid", Self::preset((0.12, 0.3), (0.5, 0.8))),
+        ("Pastel", Self::preset((0.04, 0.1), (0.85, 0.95))),
+        ("Muted", Self::preset((0.02, 0.08), (0.4, 0.75))),
+        ("Dark", Self::preset((0.06, 0.2), (0.25, 0.45))),
+    ];
+
+    const fn preset(chroma: (f32, f32), lightness: (f32, f32)) -> Self {
+        Self {
+            hue: (0., 360.),
+            chroma,
+            lightness,
+            harmonious: false,
+            seed: None,
+        }
+    }
+
+    pub fn preset_name(&self) -> Option<&'static str> {
+        Self::PRESETS
+            .iter()
+            .find(|(_, x)| x.chroma == self.chroma && x.lightness == self.lightness)
+            .map(|(name, _)| *name)
+    }
+}
+
+/// Hue offsets of the analogous, triadic, split complementary and complementary colors.
+const HARMONY_OFFSETS: [f32; 7] = [30., -30., 120., -120., 150., -150., 180.];
+
+pub struct RandomColors {
+    config: RandomConfig,
+    rng: StdRng,
+}
+
+impl RandomColors {
+    pub fn new(config: RandomConfig) -> Self {
+        Self {
+            config,
+            rng: Self::rng(config.seed),
+        }
+    }
+
+    fn rng(seed: Option<u64>) -> StdRng {
+        match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    pub fn config(&self) -> RandomConfig {
+        self.config
+    }
+
+    /// Changing the seed restarts its sequence.
+    pub fn set_config(&mut self, config: RandomConfig) {
+        if config.seed != self.config.seed {
+            self.rng = Self::rng(config.seed);
+        }
+        self.config = config;
+    }
+
+    pub fn restart(&mut self) {
+        self.rng = Self::rng(self.config.seed);
+    }
+
+    pub fn next(&mut self, current: ColorFormat) -> ColorFormat {
+        let RandomConfig {
+            hue,
+            chroma,
+            lightness,
+            harmonious,
+            ..
+        } = self.config;
+        let mut sample = |(a, b): (f32, f32)| a + (b - a) * self.rng.gen::<f32>();
+
+        let h = if harmonious {
+            let (_, _, base_hue) = current.split_oklch();
+            let offset = HARMONY_OFFSETS[(sample((0., 7.)) as usize).min(6)];
+            base_hue + offset + sample((-10., 10.))
+        } else {
+            let span = match (hue.1 - hue.0).rem_euclid(360.) {
+                x if x == 0. && hue.0 != hue.1 => 360.,
+                x => x,
+            };
+            hue.0 + sample((0., span))
+        };
+        let l = sample(lightness).clamp(0., 1.);
+        let c = sample(chroma).max(0.);
+        let rgb = oklch_to_srgb_gamut_mapped([l, c, h.rem_euclid(360.)]);
+        ColorFormat::from_srgb(rgb, 1.).to_hsl()
+    }
+}
+
+/// Parses ranges like "0.2-0.6", "330..30" or a single value.
+pub fn parse_range(s: &str) -> Option<(f32, f32)> {
+    let s = s.trim();
+    let (a, b) = s
+        .split_once("..")
+        .or_else(|| s.split_once('–'))
+        .or_else(|| s.split_once('-'))
+        .unwrap_or((s, s));
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        let config = RandomConfig {
+            hue: (330., 30.),
+            chroma: (0.1, 0.15),
+            lightness: (0.6, 0.7),
+            harmonious: false,
+            seed: Some(42),
+        };
+        let mut random = RandomColors::new(config);
+        let current = ColorFormat::Hsl(0., 0., 0.);
+        for _ in 0..200 {
+            let (l, c, h) = random.next(current).split_oklch();
+            assert!((0.59..=0.71).contains(&l), "{}", l);
+            assert!(c <= 0.16, "{}", c);
+            // gray-ish results have no meaningful hue
+            assert!(c < 0.02 || h >= 325. || h <= 35., "{}", h);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let config = RandomConfig {
+            seed: Some(7),
+            ..RandomConfig::default()
+        };
+        let current = ColorFormat::Hsl(200., 50., 50.);
+        let (mut a, mut b) = (RandomColors::new(config), RandomColors::new(config));
+        let seq_a: Vec<_> = (0..10).map(|_| a.next(current)).collect();
+        let seq_b: Vec<_> = (0..10).map(|_| b.next(current)).collect();
+        assert_eq!(seq_a, seq_b);
+        a.restart();
+        assert_eq!(a.next(current), seq_a[0]);
+    }
+
+    #[test]
+    fn harmonious() {
+        let config = RandomConfig {
+            harmonious: true,
+            seed: Some(1),
+            ..RandomConfig::default()
+        };
+        let mut random = RandomColors::new(config);
+        let current = ColorFormat::Oklch(0.6, 0.15, 100., 1.);
+        for _ in 0..100 {
+            let (_, c, h) = random.next(current).split_oklch();
+            if c < 0.05 {
+                continue;
+            }
+            let d = (h - 100.).rem_euclid(360.);
+            let d = d.min(360. - d);
+            assert!(d >= 15., "{}", h);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_range("0.2-0.6"), Some((0.2, 0.6)));
+        assert_eq!(parse_range("330..30"), Some((330., 30.)));
+        assert_eq!(parse_range(" 0.5 "), Some((0.5, 0.5)));
+        assert_eq!(parse_range("a-b"), None);
+    }
+}
@@ -1,18 +1,29 @@
 use gpui::{Context, Model, ModelContext};
 
-use crate::{ColorFormat, ColorLibrary, ColorPalette};
+use crate::{
+    ColorFormat, ColorHistory, ColorLibrary, ColorPalette, ColorVision, DesignSystem, RandomColors,
+};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MainTab {
     Home,
     Browse,
     Favorites,
+    DesignSystem,
+    Eyedropper,
+    Compare,
+    Gradient,
 }
 
 pub struct AppState {
     pub color: Model<ColorFormat>,
-    pub palette: Model<ColorPalette>,
+    pub palette: Model<ColorPalette>, // the active palette of the design system
+    pub design_system: Model<DesignSystem>,
     pub main_tab: Model<MainTab>,
     pub library: Model<Option<ColorLibrary>>,
+    pub color_vision: Model<ColorVision>,
+    pub history: Model<ColorHistory>,
+    pub random: Model<RandomColors>,
 }
 
 pub enum Event {
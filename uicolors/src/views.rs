@@ -1,13 +1,45 @@
 mod central;
 mod color_input;
+mod color_plane;
 mod color_slider;
+mod compare;
+mod eyedropper;
+mod gradient_editor;
 mod left_panel;
+mod library_grid;
+mod palette_stack;
+mod random_options;
+mod right_panel;
+mod text_field;
 mod title_bar;
 mod workspace;
 
 pub use central::*;
 pub use color_input::*;
+pub use color_plane::*;
 pub use color_slider::*;
+pub use compare::*;
+pub use eyedropper::*;
+pub use gradient_editor::*;
 pub use left_panel::*;
+pub use library_grid::*;
+pub use palette_stack::*;
+pub use random_options::*;
+pub use right_panel::*;
+pub use text_field::*;
 pub use title_bar::*;
 pub use workspace::*;
+
+/// A channel in 0..1 as a byte, for the images the sliders, planes and gradients draw.
+pub fn channel_byte(f: f32) -> u8 {
+    (f * 255.).round().clamp(0., 255.) as u8
+}
+
+/// Gray of the light and dark squares drawn behind translucent colors.
+pub fn checkerboard(x: u32, y: u32, cell: u32) -> f32 {
+    if (x / cell + y / cell) % 2 == 0 {
+        0.9
+    } else {
+        0.6
+    }
+}
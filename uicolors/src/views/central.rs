@@ -1,17 +1,75 @@
 use super::*;
-use crate::{AppState, ColorFormat, ResultExt};
+use crate::{
+    export_palettes, label_color, AppState, ColorFormat, ColorLibrary, ColorPalette, ColorVision,
+    CopyFormat, CubicBezier, ExportFormat, Harmony, MainTab, PaletteMode, ResultExt, SCALES,
+};
 use gpui::*;
 use gpui_ext::*;
-use indexmap::IndexMap;
 use std::{sync::Arc, time::Duration};
 
+/// Settings of OKLCH palettes that are typed in rather than picked.
+#[derive(Clone, Copy, PartialEq)]
+enum PaletteField {
+    Lightest,
+    Darkest,
+    LightnessCurve,
+    ChromaCurve,
+}
+
+impl PaletteField {
+    const ALL: [PaletteField; 4] = [
+        PaletteField::Lightest,
+        PaletteField::Darkest,
+        PaletteField::LightnessCurve,
+        PaletteField::ChromaCurve,
+    ];
+
+    fn text(self, palette: &ColorPalette) -> String {
+        let (lightest, darkest) = palette.lightness_range;
+        match self {
+            PaletteField::Lightest => format!("{:.2}", lightest),
+            PaletteField::Darkest => format!("{:.2}", darkest),
+            PaletteField::LightnessCurve => palette.lightness_curve.name(),
+            PaletteField::ChromaCurve => palette.chroma_curve.name(),
+        }
+    }
+
+    /// The palette with the setting of this field changed to `text`.
+    fn apply(self, palette: ColorPalette, text: &str) -> Result<ColorPalette, String> {
+        let (lightest, darkest) = palette.lightness_range;
+        let lightness = || match text.trim().parse::<f32>() {
+            Ok(x) if (0. ..=1.).contains(&x) => Ok(x),
+            _ => Err("lightness must be between 0 and 1".to_owned()),
+        };
+        let palette = match self {
+            PaletteField::Lightest => palette.lightness_range(lightness()?, darkest),
+            PaletteField::Darkest => palette.lightness_range(lightest, lightness()?),
+            PaletteField::LightnessCurve => palette.lightness_curve(CubicBezier::parse(text)?),
+            PaletteField::ChromaCurve => palette.chroma_curve(CubicBezier::parse(text)?),
+        };
+        Ok(palette)
+    }
+}
+
 pub struct Central {
     workspace: WeakView<Workspace>,
     app_state: Arc<AppState>,
     color_input: View<ColorInputView>,
+    random_options: View<RandomOptions>,
+    browse: View<LibraryGrid>,
+    favorites: View<LibraryGrid>,
+    palette_stack: View<PaletteStack>,
+    eyedropper: View<Eyedropper>,
+    compare: View<CompareView>,
+    gradient_editor: View<GradientEditor>,
     focus_handle: FocusHandle,
     btn_json_hover: bool,
+    export_format: ExportFormat,
+    swatch_menu: Option<(usize, Point<Pixels>)>, // scale of the swatch and where it was opened
+    harmony: Harmony,
+    palette_fields: [View<TextField>; 4], // in the order of PaletteField::ALL
     show_copied_msg: Model<Option<(usize, SharedString)>>, // (id, msg)
+    _subscriptions: Vec<Subscription>,
 }
 
 impl Central {
@@ -23,14 +81,80 @@ impl Central {
         app_state: Arc<AppState>,
     ) -> Self {
         let app_state0 = app_state.clone();
-        let AppState { color, palette, .. } = app_state0.as_ref();
+        let AppState {
+            color,
+            palette,
+            color_vision,
+            main_tab,
+            library,
+            history,
+            ..
+        } = app_state0.as_ref();
+        let current_palette = *palette.read(cx);
+        let palette_fields = PaletteField::ALL
+            .map(|field| cx.new_view(|cx| TextField::new(cx, field.text(&current_palette), "")));
+        let mut _subscriptions = vec![
+            cx.observe(main_tab, |_, _, cx| cx.notify()),
+            cx.observe(color, |_, _, cx| cx.notify()),
+            cx.observe(history, |_, _, cx| cx.notify()),
+            cx.observe(palette, |this, _, cx| this.update_palette_fields(cx, false)),
+        ];
+        for (field_view, field) in palette_fields.iter().zip(PaletteField::ALL) {
+            _subscriptions.push(
+                cx.subscribe(field_view, move |this, field_view, event, cx| {
+                    let text = match event {
+                        TextFieldEvent::Changed(text) | TextFieldEvent::Submit(text) => text,
+                        TextFieldEvent::Cancel => {
+                            this.update_palette_fields(cx, true);
+                            return;
+                        }
+                    };
+                    let palette = *this.app_state.palette.read(cx);
+                    let result = field.apply(palette, text);
+                    field_view.update(cx, |field_view, cx| {
+                        field_view.set_invalid(cx, result.is_err())
+                    });
+                    // applied on enter only, so the palette doesn't jump around while typing
+                    if let (Ok(palette), TextFieldEvent::Submit(_)) = (result, event) {
+                        this.update_palette(cx, |_| palette);
+                        this.update_palette_fields(cx, true);
+                    }
+                }),
+            );
+        }
         Self {
+            color_input: cx.new_view(|cx| {
+                ColorInputView::new(
+                    cx,
+                    workspace.clone(),
+                    color.clone(),
+                    palette.clone(),
+                    color_vision.clone(),
+                    library.clone(),
+                    history.clone(),
+                )
+            }),
+            random_options: cx.new_view(|cx| RandomOptions::new(cx, app_state.clone())),
+            browse: cx
+                .new_view(|cx| LibraryGrid::new(cx, workspace.clone(), app_state.clone(), false)),
+            favorites: cx
+                .new_view(|cx| LibraryGrid::new(cx, workspace.clone(), app_state.clone(), true)),
+            palette_stack: cx
+                .new_view(|cx| PaletteStack::new(cx, workspace.clone(), app_state.clone())),
+            eyedropper: cx.new_view(|cx| Eyedropper::new(cx, workspace.clone(), app_state.clone())),
+            compare: cx.new_view(|cx| CompareView::new(cx, workspace.clone(), app_state.clone())),
+            gradient_editor: cx
+                .new_view(|cx| GradientEditor::new(cx, workspace.clone(), app_state.clone())),
             workspace,
             app_state,
-            color_input: cx.new_view(|cx| ColorInputView::new(cx, color.clone(), palette.clone())),
             focus_handle: cx.focus_handle(),
             btn_json_hover: false,
+            export_format: ExportFormat::Json,
+            swatch_menu: None,
+            harmony: Harmony::Complementary,
+            palette_fields,
             show_copied_msg: cx.new_model(|cx| None),
+            _subscriptions,
         }
     }
 
@@ -63,6 +187,443 @@ impl Central {
         })
         .detach();
     }
+
+    /// What clicking a swatch copies, saved with the library.
+    fn copy_format(&self, cx: &AppContext) -> CopyFormat {
+        let library = self.app_state.library.read(cx).as_ref();
+        library.map_or(CopyFormat::default(), |x| x.copy_format)
+    }
+
+    fn set_copy_format(&mut self, cx: &mut ViewContext<Self>, format: CopyFormat) {
+        self.app_state.library.update(cx, |library, cx| {
+            library
+                .get_or_insert_with(ColorLibrary::default)
+                .copy_format = format;
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn copy_color(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat, format: CopyFormat) {
+        let code = format.format(color);
+        self.set_copied_msg(
+            cx,
+            SharedString::from(format!("{} copied to clipboard!", &code)),
+        );
+        cx.write_to_clipboard(ClipboardItem::new(code));
+    }
+
+    fn close_swatch_menu(&mut self, cx: &mut ViewContext<Self>) {
+        self.swatch_menu = None;
+        cx.notify();
+    }
+
+    /// Shows the settings of the palette in the fields, except in the one being typed in.
+    fn update_palette_fields(&mut self, cx: &mut ViewContext<Self>, include_focused: bool) {
+        let palette = *self.app_state.palette.read(cx);
+        for (field_view, field) in self.palette_fields.clone().iter().zip(PaletteField::ALL) {
+            if !include_focused && field_view.read(cx).is_focused(cx) {
+                continue;
+            }
+            field_view.update(cx, |field_view, cx| {
+                field_view.set_text(cx, field.text(&palette));
+                field_view.set_invalid(cx, false);
+            });
+        }
+    }
+
+    fn update_palette(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        f: impl FnOnce(ColorPalette) -> ColorPalette,
+    ) {
+        self.app_state.palette.update(cx, |palette, cx| {
+            *palette = f(*palette);
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn render_palette_options(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let option = |id: &'static str, label: SharedString, selected: bool| {
+            option_button(id, label, selected, &palette)
+        };
+
+        let row = div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .child(
+                option("mode-hsl", "HSL".into(), palette.mode == PaletteMode::Hsl).on_click(
+                    cx.listener(|this, _, cx| {
+                        this.update_palette(cx, |p| p.mode(PaletteMode::Hsl))
+                    }),
+                ),
+            )
+            .child(
+                option(
+                    "mode-oklch",
+                    "OKLCH".into(),
+                    palette.mode == PaletteMode::Oklch,
+                )
+                .on_click(cx.listener(|this, _, cx| {
+                    this.update_palette(cx, |p| p.mode(PaletteMode::Oklch))
+                })),
+            );
+        if palette.mode != PaletteMode::Oklch {
+            return row;
+        }
+        let label = |text: &'static str| {
+            div()
+                .text_size(px(12.))
+                .text_color(palette.at_darkness(500))
+                .child(text)
+        };
+        let [lightest, darkest, lightness_curve, chroma_curve] = self.palette_fields.clone();
+        let settings = div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .child(label("Lightness from"))
+            .child(div().w(px(56.)).child(lightest))
+            .child(label("to"))
+            .child(div().w(px(56.)).child(darkest))
+            .child(label("curves"))
+            .child(div().w(px(220.)).child(lightness_curve))
+            .child(div().w(px(220.)).child(chroma_curve));
+        let row = row
+            .child(div().w(px(16.)))
+            .child(
+                option(
+                    "lightness-curve",
+                    format!("Lightness: {}", palette.lightness_curve.name()).into(),
+                    false,
+                )
+                .on_click(cx.listener(|this, _, cx| {
+                    this.update_palette(cx, |p| p.lightness_curve(p.lightness_curve.next_preset()))
+                })),
+            )
+            .child(
+                option(
+                    "chroma-curve",
+                    format!("Chroma: {}", palette.chroma_curve.name()).into(),
+                    false,
+                )
+                .on_click(cx.listener(|this, _, cx| {
+                    this.update_palette(cx, |p| p.chroma_curve(p.chroma_curve.next_preset()))
+                })),
+            );
+        div()
+            .flex_center()
+            .flex_col()
+            .gap(px(8.))
+            .child(row)
+            .child(settings)
+    }
+
+    fn render_copy_options(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let copy_format = self.copy_format(cx);
+        div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .child(
+                div()
+                    .text_size(px(12.))
+                    .text_color(palette.at_darkness(500))
+                    .child("Click copies"),
+            )
+            .children(CopyFormat::ALL.iter().map(|x| {
+                let x = *x;
+                let id = ("copy-format", x as usize);
+                option_button(id, x.name(), x == copy_format, &palette)
+                    .on_click(cx.listener(move |this, _, cx| this.set_copy_format(cx, x)))
+            }))
+    }
+
+    /// Right click menu of a palette swatch, to copy its color in any format.
+    fn render_swatch_menu(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let (scale, position) = self.swatch_menu?;
+        let palette = *self.app_state.palette.read(cx);
+        let color = ColorFormat::from(palette.at_darkness(scale));
+        let copy_format = self.copy_format(cx);
+        let rows = CopyFormat::ALL.into_iter().map(|format| {
+            let code = format.format(color);
+            let is_default = format == copy_format;
+            div()
+                .id(("swatch-menu-item", format as usize))
+                .h(px(28.))
+                .px(px(8.))
+                .rounded(px(4.))
+                .flex()
+                .flex_row()
+                .items_center()
+                .gap(px(8.))
+                .cursor_pointer()
+                .hover(|st| st.bg(rgb(0x2a2a2a)))
+                .on_click(cx.listener(move |this, _, cx| {
+                    this.copy_color(cx, color, format);
+                    this.close_swatch_menu(cx);
+                }))
+                .child(
+                    div()
+                        .w(px(64.))
+                        .text_color(palette.at_darkness(300))
+                        .child(format.name()),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .font("Monaspace Xenon")
+                        .text_size(px(12.))
+                        .text_color(palette.at_darkness(500))
+                        .child(code),
+                )
+                .child(
+                    // the default is what a plain click copies
+                    div()
+                        .id(("swatch-menu-default", format as usize))
+                        .px(px(4.))
+                        .text_color(palette.at_darkness(if is_default { 300 } else { 700 }))
+                        .child(if is_default { "★" } else { "☆" })
+                        .on_click(cx.listener(move |this, _, cx| {
+                            cx.stop_propagation();
+                            this.set_copy_format(cx, format);
+                        })),
+                )
+        });
+
+        Some(
+            overlay().position(position).child(
+                div()
+                    .w(px(320.))
+                    .p(px(4.))
+                    .rounded(px(6.))
+                    .bg(rgb(0x181818))
+                    .border_1()
+                    .border_color(rgb(0x333333))
+                    .text_size(px(13.))
+                    .flex()
+                    .flex_col()
+                    .on_mouse_down_out(cx.listener(|this, _, cx| this.close_swatch_menu(cx)))
+                    .children(rows),
+            ),
+        )
+    }
+
+    fn render_vision_options(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let vision = *self.app_state.color_vision.read(cx);
+        div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .children(ColorVision::ALL.iter().map(|x| {
+                let x = *x;
+                option_button(x.name(), x.name(), x == vision, &palette).on_click(cx.listener(
+                    move |this, _, cx| {
+                        this.app_state.color_vision.update(cx, |vision, cx| {
+                            *vision = x;
+                            cx.notify();
+                        });
+                        cx.notify();
+                    },
+                ))
+            }))
+    }
+}
+
+impl Central {
+    /// Exports every palette of the design system, which is the edited one until others are added.
+    fn export(&self, cx: &AppContext) -> String {
+        let palettes = self.app_state.design_system.read(cx).export_palettes();
+        export_palettes(&palettes, self.export_format)
+    }
+
+    fn save_export(&mut self, cx: &mut ViewContext<Self>) {
+        let code = self.export(cx);
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let extension = self.export_format.extension();
+        let path = cx.prompt_for_new_path(&dir);
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Some(mut path)) = path.await else {
+                return;
+            };
+            if path.extension().is_none() {
+                path.set_extension(extension);
+            }
+            if std::fs::write(&path, code).log_err().is_none() {
+                return;
+            }
+            if let Some(this) = this.upgrade() {
+                this.update(&mut cx, |this, cx| {
+                    let msg = format!("Saved to {}", path.display());
+                    this.set_copied_msg(cx, SharedString::from(msg));
+                })
+                .log_err();
+            }
+        })
+        .detach();
+    }
+
+    fn render_recent_colors(&self, cx: &mut ViewContext<Self>) -> Div {
+        const COUNT: usize = 16;
+        let recent = self.app_state.history.read(cx).recent(COUNT + 1);
+        let vision = *self.app_state.color_vision.read(cx);
+        let palette = *self.app_state.palette.read(cx);
+
+        // the first one is the current color
+        div()
+            .h(px(40.))
+            .flex_center()
+            .flex_row()
+            .gap(px(6.))
+            .when(recent.len() > 1, |div0| {
+                div0.child(
+                    div()
+                        .text_size(px(12.))
+                        .text_color(palette.at_darkness(500))
+                        .child("Recent"),
+                )
+            })
+            .children(recent.into_iter().skip(1).enumerate().map(|(i, color)| {
+                div()
+                    .id(("recent-color", i))
+                    .size(px(20.))
+                    .rounded_full()
+                    .cursor_pointer()
+                    .border_1()
+                    .border_color(palette.at_darkness(800))
+                    .bg(vision.simulate_hsla(color.canonicalize()))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.workspace
+                            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+                            .log_err();
+                    }))
+            }))
+    }
+
+    /// Palette rows of the colors related to the current one; clicking a row makes it the main color.
+    fn render_harmonies(&self, cx: &mut ViewContext<Self>) -> Div {
+        let color = *self.app_state.color.read(cx);
+        let vision = *self.app_state.color_vision.read(cx);
+        let palette = *self.app_state.palette.read(cx);
+
+        let options = div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .child(
+                div()
+                    .text_size(px(12.))
+                    .text_color(palette.at_darkness(500))
+                    .child("Harmony"),
+            )
+            .children(Harmony::ALL.iter().map(|x| {
+                let x = *x;
+                option_button(x.name(), x.name(), x == self.harmony, &palette).on_click(
+                    cx.listener(move |this, _, cx| {
+                        this.harmony = x;
+                        cx.notify();
+                    }),
+                )
+            }));
+        let rows = self
+            .harmony
+            .colors(color)
+            .into_iter()
+            .enumerate()
+            .map(|(i, related)| {
+                let related_palette = palette.with_base(related.canonicalize());
+                div()
+                    .id(("harmony-row", i))
+                    .flex()
+                    .flex_row()
+                    .gap(px(6.))
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.workspace
+                            .update(cx, |workspace, cx| workspace.set_color_format(cx, related))
+                            .log_err();
+                    }))
+                    .children(SCALES.iter().map(|sc| {
+                        div()
+                            .w(px(72.))
+                            .h(px(24.))
+                            .rounded(px(4.))
+                            .bg(vision.simulate_hsla(related_palette.at_darkness(*sc)))
+                    }))
+            });
+        div()
+            .flex_center()
+            .flex_col()
+            .gap(px(6.))
+            .child(options)
+            .children(rows)
+    }
+
+    fn render_export_options(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        div()
+            .flex_center()
+            .flex_row()
+            .gap(px(8.))
+            .children(ExportFormat::ALL.iter().map(|x| {
+                let x = *x;
+                option_button(x.name(), x.name(), x == self.export_format, &palette).on_click(
+                    cx.listener(move |this, _, cx| {
+                        this.export_format = x;
+                        cx.notify();
+                    }),
+                )
+            }))
+    }
+}
+
+/// A color being dragged, e.g. from a palette swatch onto the library list.
+#[derive(Clone)]
+pub struct DraggedColor(pub ColorFormat);
+
+impl Render for DraggedColor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .size(px(32.))
+            .rounded(px(6.))
+            .border_1()
+            .border_color(rgb(0xffffff))
+            .bg(self.0.canonicalize())
+    }
+}
+
+pub fn option_button(
+    id: impl Into<ElementId>,
+    label: impl Into<SharedString>,
+    selected: bool,
+    palette: &ColorPalette,
+) -> Stateful<Div> {
+    div()
+        .id(id)
+        .h(px(28.))
+        .px(px(12.))
+        .flex_center()
+        .rounded(px(4.))
+        .cursor_pointer()
+        .text_size(px(13.))
+        .text_color(if selected {
+            palette.at_darkness(900)
+        } else {
+            palette.at_darkness(300)
+        })
+        .bg(if selected {
+            palette.at_darkness(300)
+        } else {
+            palette.at_darkness(900)
+        })
+        .child(label.into())
 }
 
 impl FocusableView for Central {
@@ -71,10 +632,30 @@ impl FocusableView for Central {
     }
 }
 
-static SCALES: [usize; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
-
 impl Render for Central {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        match *self.app_state.main_tab.read(cx) {
+            MainTab::Home => self.render_home(cx),
+            MainTab::Browse => div().size_full().child(self.browse.clone()),
+            MainTab::Favorites => div().size_full().child(self.favorites.clone()),
+            MainTab::DesignSystem => div().size_full().child(self.palette_stack.clone()),
+            MainTab::Eyedropper => div().size_full().child(self.eyedropper.clone()),
+            MainTab::Compare => div().size_full().child(self.compare.clone()),
+            MainTab::Gradient => div().size_full().child(self.gradient_editor.clone()),
+        }
+    }
+}
+
+impl Central {
+    fn render_home(&mut self, cx: &mut ViewContext<Self>) -> Div {
+        let palette_options = self.render_palette_options(cx);
+        let vision_options = self.render_vision_options(cx);
+        let copy_options = self.render_copy_options(cx);
+        let swatch_menu = self.render_swatch_menu(cx);
+        let export_options = self.render_export_options(cx);
+        let recent_colors = self.render_recent_colors(cx);
+        let harmonies = self.render_harmonies(cx);
+        let vision = *self.app_state.color_vision.read(cx);
         let palette = self.app_state.palette.read(cx);
 
         div()
@@ -88,15 +669,27 @@ impl Render for Central {
                     .w_full()
                     .flex_center()
                     .text_color(palette.at_darkness(400))
-                    .child("Select a color or press spacebar for random one."),
+                    .child("Select or paste a color, or press spacebar for a random one."),
             )
             .child(div().flex_center().child(self.color_input.clone()))
+            .child(recent_colors)
+            .children(swatch_menu)
             .child(
                 div()
                     .size_full()
                     .flex_center()
                     .flex_col()
-                    .child(div().h(px(100.)))
+                    .child(
+                        div()
+                            .h(px(200.))
+                            .flex_center()
+                            .flex_col()
+                            .gap(px(8.))
+                            .child(palette_options)
+                            .child(vision_options)
+                            .child(copy_options)
+                            .child(self.random_options.clone()),
+                    )
                     .child(
                         div()
                             .h(px(100.))
@@ -106,34 +699,36 @@ impl Render for Central {
                             .gap(px(6.))
                             .children({
                                 SCALES.iter().map(|sc| {
-                                    let c = palette.at_darkness(*sc);
-                                    let text_color = if c.l < 0.5 {
-                                        hsla(0., 0.5, 1., 1.)
-                                    } else {
-                                        hsla(0., 0.5, 0., 1.)
-                                    };
+                                    let scale = *sc;
+                                    let c = palette.at_darkness(scale);
+                                    let shown = vision.simulate_hsla(c);
+                                    let text_color = label_color(shown);
                                     div()
                                         .id(*sc)
                                         .w(px(72.))
                                         .h(px(100.))
                                         .rounded(px(8.))
-                                        .bg(c)
+                                        .bg(shown)
                                         .flex()
                                         .flex_col()
                                         .justify_end()
                                         .items_center()
                                         .cursor_pointer()
                                         .on_click(cx.listener(move |this, _, cx| {
-                                            let code = ColorFormat::from(c).to_rgb().to_string();
-                                            this.set_copied_msg(
-                                                cx,
-                                                SharedString::from(format!(
-                                                    "{} copied to clipboard!",
-                                                    &code
-                                                )),
-                                            );
-                                            cx.write_to_clipboard(ClipboardItem::new(code));
+                                            let format = this.copy_format(cx);
+                                            this.copy_color(cx, ColorFormat::from(c), format);
                                         }))
+                                        .on_mouse_down(
+                                            MouseButton::Right,
+                                            cx.listener(move |this, ev: &MouseDownEvent, cx| {
+                                                this.swatch_menu = Some((scale, ev.position));
+                                                cx.notify();
+                                            }),
+                                        )
+                                        .on_drag(
+                                            DraggedColor(ColorFormat::from(c)),
+                                            |dragged, cx| cx.new_view(|_| dragged.clone()),
+                                        )
                                         .child(
                                             div()
                                                 .flex_center()
@@ -160,6 +755,8 @@ impl Render for Central {
                                 })
                             }),
                     )
+                    .child(div().h(px(16.)))
+                    .child(harmonies)
                     .child({
                         let text_color = palette.at_darkness(300);
                         let div0 = div()
@@ -191,45 +788,59 @@ impl Render for Central {
                             _ => div0,
                         }
                     })
+                    .child(export_options)
                     .child(
                         div()
-                            .id("btn-json")
-                            .focusable()
-                            .cursor_pointer()
-                            .on_hover(cx.listener(|this, is_hover, cx| {
-                                if this.btn_json_hover != *is_hover {
-                                    this.btn_json_hover = *is_hover;
-                                    cx.refresh();
-                                }
-                            }))
-                            .on_click(cx.listener(|this, ev, cx| {
-                                let palette = this.app_state.palette.read(cx);
-                                let map_colors: IndexMap<usize, String> =
-                                    IndexMap::from_iter(SCALES.iter().map(|sc| {
-                                        let color = palette.at_darkness(*sc);
-                                        let code = ColorFormat::from(color).to_rgb().to_string();
-                                        (*sc, format!("{}", code))
-                                    }));
-                                if let Ok(json) = serde_json::to_string_pretty(&map_colors) {
-                                    cx.write_to_clipboard(ClipboardItem::new(json));
-                                    this.set_copied_msg(
-                                        cx,
-                                        SharedString::from(
-                                            "Color palette get copied to clipboard!",
-                                        ),
-                                    );
-                                };
-                            }))
+                            .h(px(60.))
                             .flex_center()
-                            .w(px(200.))
-                            .h(px(40.))
-                            .rounded(px(4.))
-                            .bg(if self.btn_json_hover {
-                                palette.at_darkness(200)
-                            } else {
-                                palette.at_darkness(300)
-                            })
-                            .child("Copy as JSON"),
+                            .flex_row()
+                            .gap(px(8.))
+                            .child(
+                                div()
+                                    .id("btn-copy")
+                                    .focusable()
+                                    .cursor_pointer()
+                                    .on_hover(cx.listener(|this, is_hover, cx| {
+                                        if this.btn_json_hover != *is_hover {
+                                            this.btn_json_hover = *is_hover;
+                                            cx.refresh();
+                                        }
+                                    }))
+                                    .on_click(cx.listener(|this, ev, cx| {
+                                        let code = this.export(cx);
+                                        cx.write_to_clipboard(ClipboardItem::new(code));
+                                        this.set_copied_msg(
+                                            cx,
+                                            SharedString::from(
+                                                "Color palette get copied to clipboard!",
+                                            ),
+                                        );
+                                    }))
+                                    .flex_center()
+                                    .w(px(200.))
+                                    .h(px(40.))
+                                    .rounded(px(4.))
+                                    .bg(if self.btn_json_hover {
+                                        palette.at_darkness(200)
+                                    } else {
+                                        palette.at_darkness(300)
+                                    })
+                                    .child(format!("Copy as {}", self.export_format.name())),
+                            )
+                            .child(
+                                div()
+                                    .id("btn-save")
+                                    .cursor_pointer()
+                                    .on_click(cx.listener(|this, _, cx| this.save_export(cx)))
+                                    .flex_center()
+                                    .w(px(120.))
+                                    .h(px(40.))
+                                    .rounded(px(4.))
+                                    .text_color(palette.at_darkness(300))
+                                    .bg(palette.at_darkness(900))
+                                    .hover(|st| st.bg(palette.at_darkness(800)))
+                                    .child("Save to file"),
+                            ),
                     ),
             )
     }
@@ -4,46 +4,518 @@ use gpui_ext::*;
 use tracing::instrument::WithSubscriber;
 
 pub struct ColorInputView {
+    workspace: WeakView<Workspace>,
     c: Model<Hsla>,
     color: Model<ColorFormat>,
     palette: Model<ColorPalette>,
+    vision: Model<ColorVision>,
+    library: Model<Option<ColorLibrary>>,
+    history: Model<ColorHistory>,
+    name_field: View<TextField>,
+    hex_field: View<TextField>,
+    hue_field: View<TextField>,
+    saturation_field: View<TextField>,
+    lightness_field: View<TextField>,
+    alpha_field: View<TextField>,
+    error: Option<String>, // why the text being typed is not a color
+    preview: Option<(Field, ColorFormat)>, // color being typed, set on enter
+    gamut: Gamut,          // where out of gamut colors are mapped to
+    gamut_mapping: GamutMapping,
     hue_slider: View<ColorSlider>,
     saturation_slider: View<ColorSlider>,
     lightness_slider: View<ColorSlider>,
+    alpha_slider: View<ColorSlider>,
+    plane: View<ColorPlane>,
     focus_handle: FocusHandle,
     _subscriptions: Vec<Subscription>,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Hex,
+    Hue,
+    Saturation,
+    Lightness,
+    Alpha,
+}
+
+impl Field {
+    const ALL: [Field; 5] = [
+        Field::Hex,
+        Field::Hue,
+        Field::Saturation,
+        Field::Lightness,
+        Field::Alpha,
+    ];
+
+    fn text(self, color: ColorFormat) -> String {
+        // rounded for display only; the color keeps its exact channels
+        let (h, s, l) = color.to_hsl().split_hsl();
+        let value = match self {
+            Field::Hex => return color.to_rgb().to_string().to_uppercase(),
+            Field::Hue => h,
+            Field::Saturation => s,
+            Field::Lightness => l,
+            Field::Alpha => color.alpha() * 100.,
+        };
+        let value = value.round() as u16;
+        // 359.6 rounds to the same hue as 0
+        let value = if self == Field::Hue {
+            value % 360
+        } else {
+            value
+        };
+        value.to_string()
+    }
+
+    /// A number sets the channel of the field, clamped to its range; anything else is parsed as a
+    /// whole color.
+    fn parse(self, text: &str, current: ColorFormat) -> Result<ColorFormat, String> {
+        let text = text.trim();
+        if self == Field::Hex {
+            let bare_hex = text.chars().all(|c| c.is_ascii_hexdigit());
+            if bare_hex && matches!(text.len(), 3 | 4 | 6 | 8) {
+                return ColorFormat::parse(&format!("#{}", text)).map_err(|err| err.to_string());
+            }
+        } else if let Some(n) = text.parse::<f32>().ok().filter(|x| x.is_finite()) {
+            let (h, s, l) = current.to_hsl().split_hsl();
+            let a = current.alpha() * 100.;
+            let max = if self == Field::Hue { 360. } else { 100. };
+            let n = n.clamp(0., max);
+            let (h, s, l, a) = match self {
+                Field::Hue => (n, s, l, a),
+                Field::Saturation => (h, n, l, a),
+                Field::Lightness => (h, s, n, a),
+                _ => (h, s, l, n),
+            };
+            return Ok(ColorFormat::Hsla(h, s, l, a).opaque_if_possible());
+        }
+        ColorFormat::parse(text).map_err(|err| err.to_string())
+    }
+}
+
 impl ColorInputView {
     pub fn new(
         cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
         color: Model<ColorFormat>,
         palette: Model<ColorPalette>,
+        vision: Model<ColorVision>,
+        library: Model<Option<ColorLibrary>>,
+        history: Model<ColorHistory>,
     ) -> Self {
         let c = cx.new_model(|cx| color.read(cx).canonicalize());
-        let _subscriptions = vec![
+        let name = Self::library_name(cx, &library, *color.read(cx));
+        let name_field = cx.new_view(|cx| TextField::new(cx, name.unwrap_or_default(), "Name"));
+        let current = *color.read(cx);
+        let [hex_field, hue_field, saturation_field, lightness_field, alpha_field] =
+            Field::ALL.map(|field| cx.new_view(|cx| TextField::new(cx, field.text(current), "")));
+        let mut _subscriptions = vec![
             cx.observe(&color, Self::handle_color_change),
             cx.subscribe(&c, Self::handle_color_slider_event),
+            cx.observe(&vision, |_, _, cx| cx.notify()),
+            cx.observe(&library, |_, _, cx| cx.notify()),
+            cx.subscribe(&name_field, |this, _, event, cx| {
+                if let TextFieldEvent::Submit(_) = event {
+                    this.save_to_library(cx);
+                }
+            }),
         ];
+        for (field_view, field) in [
+            &hex_field,
+            &hue_field,
+            &saturation_field,
+            &lightness_field,
+            &alpha_field,
+        ]
+        .into_iter()
+        .zip(Field::ALL)
+        {
+            _subscriptions.push(Self::subscribe_field(cx, field_view, field));
+        }
 
         Self {
+            workspace,
             c: c.clone(),
             color,
             palette,
+            vision: vision.clone(),
+            library,
+            history,
+            name_field,
+            hex_field,
+            hue_field,
+            saturation_field,
+            lightness_field,
+            alpha_field,
+            error: None,
+            preview: None,
+            gamut: Gamut::Srgb,
+            gamut_mapping: GamutMapping::ReduceChroma,
             focus_handle: cx.focus_handle(),
-            hue_slider: ColorSlider::new(cx, ColorScale::Hue, c.clone()),
-            saturation_slider: ColorSlider::new(cx, ColorScale::Saturation, c.clone()),
-            lightness_slider: ColorSlider::new(cx, ColorScale::Lightness, c.clone()),
+            hue_slider: ColorSlider::new(cx, ColorScale::Hue, c.clone(), vision.clone()),
+            saturation_slider: ColorSlider::new(
+                cx,
+                ColorScale::Saturation,
+                c.clone(),
+                vision.clone(),
+            ),
+            lightness_slider: ColorSlider::new(
+                cx,
+                ColorScale::Lightness,
+                c.clone(),
+                vision.clone(),
+            ),
+            alpha_slider: ColorSlider::new(cx, ColorScale::Alpha, c.clone(), vision.clone()),
+            plane: ColorPlane::new(
+                cx,
+                PlaneKind::SaturationLightness,
+                c.clone(),
+                vision.clone(),
+            ),
             _subscriptions,
         }
     }
 
+    fn field_view(&self, field: Field) -> &View<TextField> {
+        match field {
+            Field::Hex => &self.hex_field,
+            Field::Hue => &self.hue_field,
+            Field::Saturation => &self.saturation_field,
+            Field::Lightness => &self.lightness_field,
+            Field::Alpha => &self.alpha_field,
+        }
+    }
+
+    fn subscribe_field(
+        cx: &mut ViewContext<Self>,
+        field_view: &View<TextField>,
+        field: Field,
+    ) -> Subscription {
+        // typing only checks and previews the text, so that the color and its history change
+        // once, on enter
+        cx.subscribe(field_view, move |this, field_view, event, cx| {
+            let (text, submit) = match event {
+                TextFieldEvent::Changed(text) => (text, false),
+                TextFieldEvent::Submit(text) => (text, true),
+                TextFieldEvent::Cancel => {
+                    this.error = None;
+                    this.preview = None;
+                    this.update_fields(cx, true);
+                    cx.notify();
+                    return;
+                }
+            };
+            let current = *this.color.read(cx);
+            let result = field.parse(text, current);
+            field_view.update(cx, |field_view, cx| {
+                field_view.set_invalid(cx, result.is_err())
+            });
+            match result {
+                Ok(color) if submit => {
+                    this.error = None;
+                    this.preview = None;
+                    this.set_color(cx, color);
+                    // show the color as it was understood
+                    this.update_fields(cx, true);
+                }
+                Ok(color) => {
+                    this.error = None;
+                    this.preview = Some((field, color));
+                }
+                Err(err) => {
+                    this.error = Some(err);
+                    this.preview = None;
+                }
+            }
+            cx.notify();
+        })
+    }
+
+    fn set_color(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat) {
+        self.workspace
+            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+            .log_err();
+    }
+
+    /// Shows the current color in the fields, except in the one being typed in.
+    fn update_fields(&mut self, cx: &mut ViewContext<Self>, include_focused: bool) {
+        let color = *self.color.read(cx);
+        for field in Field::ALL {
+            let field_view = self.field_view(field).clone();
+            if !include_focused && field_view.read(cx).is_focused(cx) {
+                continue;
+            }
+            field_view.update(cx, |field_view, cx| {
+                field_view.set_text(cx, field.text(color));
+                field_view.set_invalid(cx, false);
+            });
+        }
+    }
+
     fn handle_color_change(&mut self, color: Model<ColorFormat>, cx: &mut ViewContext<Self>) {
         let new_color = color.read(cx).canonicalize();
+        self.preview = None;
         self.c.update(cx, |c, cx| {
             *c = new_color;
             cx.notify();
         });
+        self.update_fields(cx, false);
+
+        // show the library name of the new color, unless the user is typing one
+        if !self.name_field.read(cx).is_focused(cx) {
+            let name = Self::library_name(cx, &self.library, *color.read(cx));
+            self.name_field
+                .update(cx, |field, cx| field.set_text(cx, name.unwrap_or_default()));
+        }
+    }
+
+    fn library_name(
+        cx: &AppContext,
+        library: &Model<Option<ColorLibrary>>,
+        color: ColorFormat,
+    ) -> Option<String> {
+        let library = library.read(cx).as_ref()?;
+        let index = library.position(color)?;
+        library.items[index].name.clone()
+    }
+
+    fn save_to_library(&mut self, cx: &mut ViewContext<Self>) {
+        let color = *self.color.read(cx);
+        let name = self.name_field.read(cx).text().trim().to_owned();
+        let name = (!name.is_empty()).then_some(name);
+        self.library.update(cx, |library, cx| {
+            library
+                .get_or_insert_with(ColorLibrary::default)
+                .save_color(color, name);
+            cx.notify();
+        });
+    }
+
+    fn render_plane(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.palette.read(cx);
+        let kind = self.plane.read(cx).kind();
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(6.))
+            .child(div().size(px(200.)).child(self.plane.clone()))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(4.))
+                    .children(PlaneKind::ALL.iter().map(|x| {
+                        let x = *x;
+                        option_button(x.name(), x.name(), x == kind, &palette).on_click(
+                            cx.listener(move |this, _, cx| {
+                                this.plane.update(cx, |plane, cx| plane.set_kind(cx, x));
+                                cx.notify();
+                            }),
+                        )
+                    })),
+            )
+    }
+
+    /// A warning for colors that the screen can't show, with the color mapped into a gamut.
+    fn render_gamut_warning(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let color = *self.color.read(cx);
+        if Gamut::Srgb.contains(color) {
+            return None;
+        }
+        let palette = *self.palette.read(cx);
+        let vision = *self.vision.read(cx);
+        let badge = if Gamut::DisplayP3.contains(color) {
+            "Out of sRGB gamut"
+        } else {
+            "Out of sRGB and Display P3 gamuts"
+        };
+        // a Display P3 color has nothing to be mapped to when Display P3 is the target
+        let fits = self.gamut.contains(color);
+        let mapped = self.gamut.map(color, self.gamut_mapping);
+        let swatch = |color: ColorFormat| {
+            div()
+                .size(px(28.))
+                .rounded(px(4.))
+                .bg(vision.simulate_hsla(color.canonicalize()))
+        };
+        let label = |text: &'static str| {
+            div()
+                .text_size(px(12.))
+                .text_color(palette.at_darkness(500))
+                .child(text)
+        };
+
+        Some(
+            div()
+                .w_full()
+                .py(px(6.))
+                .flex()
+                .flex_col()
+                .gap(px(6.))
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap(px(8.))
+                        .child(
+                            div()
+                                .px(px(8.))
+                                .py(px(2.))
+                                .rounded(px(4.))
+                                .bg(rgb(0xf59e0b))
+                                .text_color(rgb(0x000000))
+                                .text_size(px(12.))
+                                .child(format!("⚠ {}", badge)),
+                        )
+                        .child(swatch(color))
+                        .when(fits, |div0| div0.child(label("Fits the selected gamut")))
+                        .when(!fits, |div0| {
+                            div0.child(label("→"))
+                                .child(swatch(mapped))
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .font("Monaspace Xenon")
+                                        .text_size(px(12.))
+                                        .text_color(palette.at_darkness(300))
+                                        .child(mapped.to_string()),
+                                )
+                                .child(
+                                    option_button("use-mapped-color", "Use", false, &palette)
+                                        .on_click(cx.listener(move |this, _, cx| {
+                                            this.set_color(cx, mapped)
+                                        })),
+                                )
+                        }),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap(px(4.))
+                        .child(label("Map into"))
+                        .children(Gamut::ALL.into_iter().map(|gamut| {
+                            option_button(gamut.name(), gamut.name(), gamut == self.gamut, &palette)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.gamut = gamut;
+                                    cx.notify();
+                                }))
+                        }))
+                        .child(label("by"))
+                        .children(GamutMapping::ALL.into_iter().map(|mapping| {
+                            let selected = mapping == self.gamut_mapping;
+                            option_button(mapping.name(), mapping.name(), selected, &palette)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.gamut_mapping = mapping;
+                                    cx.notify();
+                                }))
+                        })),
+                ),
+        )
+    }
+
+    fn render_save_row(&self, cx: &mut ViewContext<Self>) -> Div {
+        let color = *self.color.read(cx);
+        let palette = *self.palette.read(cx);
+        let saved = self
+            .library
+            .read(cx)
+            .as_ref()
+            .and_then(|library| library.position(color))
+            .is_some();
+        let nearest = nearest_named_color(color, self.library.read(cx).as_ref(), DeltaE::Ciede2000);
+
+        div()
+            .w_full()
+            .h(px(40.))
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap(px(8.))
+            .child(div().flex_1().child(self.name_field.clone()))
+            .child(
+                div()
+                    .id("save-to-library")
+                    .h(px(28.))
+                    .px(px(12.))
+                    .flex_center()
+                    .rounded(px(4.))
+                    .cursor_pointer()
+                    .text_size(px(13.))
+                    .text_color(palette.at_darkness(900))
+                    .bg(palette.at_darkness(300))
+                    .hover(|st| st.bg(palette.at_darkness(200)))
+                    .child(if saved { "Rename" } else { "Save to library" })
+                    .on_click(cx.listener(|this, _, cx| this.save_to_library(cx))),
+            )
+            .when(saved, |div0| {
+                div0.child(
+                    div()
+                        .text_size(px(12.))
+                        .text_color(palette.at_darkness(400))
+                        .child("In library"),
+                )
+            })
+            .when_some(nearest.filter(|_| !saved), |div0, nearest| {
+                let metric = DeltaE::Ciede2000;
+                div0.child(
+                    div()
+                        .id("nearest-named-color")
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap(px(4.))
+                        .cursor_pointer()
+                        .text_size(px(12.))
+                        .text_color(palette.at_darkness(400))
+                        .hover(|st| st.text_color(palette.at_darkness(200)))
+                        .child(
+                            div()
+                                .size(px(12.))
+                                .rounded(px(2.))
+                                .bg(nearest.color.canonicalize()),
+                        )
+                        .child(format!(
+                            "≈ {} {} {:.1}",
+                            nearest.name,
+                            metric.name(),
+                            nearest.distance
+                        ))
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.set_color(cx, nearest.color)),
+                        ),
+                )
+            })
+    }
+
+    /// Tab and shift-tab move the focus between the sliders.
+    fn handle_key_down(&mut self, ev: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        if ev.keystroke.key != "tab" {
+            return;
+        }
+        let sliders = [
+            &self.hue_slider,
+            &self.saturation_slider,
+            &self.lightness_slider,
+            &self.alpha_slider,
+        ];
+        let Some(i) = sliders
+            .iter()
+            .position(|x| x.read(cx).focus_handle(cx).is_focused(cx))
+        else {
+            return;
+        };
+        let n = sliders.len();
+        let next = if ev.keystroke.modifiers.shift {
+            (i + n - 1) % n
+        } else {
+            (i + 1) % n
+        };
+        cx.focus_view(sliders[next]);
+        cx.stop_propagation();
     }
 
     fn handle_color_slider_event(
@@ -54,14 +526,14 @@ impl ColorInputView {
     ) {
         match event {
             ColorSliderEvent::ColorChanged(new_color) => {
-                self.color.update(cx, |color, cx| {
-                    *color = ColorFormat::from(*new_color);
-                    cx.notify();
-                });
-                self.palette.update(cx, |palette, cx| {
-                    *palette = ColorPalette::from(*new_color);
-                    cx.notify();
-                })
+                self.set_color(cx, ColorFormat::from(*new_color));
+            }
+            ColorSliderEvent::GestureStarted => {
+                self.history
+                    .update(cx, |history, _| history.begin_gesture());
+            }
+            ColorSliderEvent::GestureEnded => {
+                self.history.update(cx, |history, _| history.end_gesture());
             }
         };
         cx.refresh()
@@ -76,9 +548,16 @@ impl FocusableView for ColorInputView {
 
 impl Render for ColorInputView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let color = self.color.read(cx);
-        let (h, s, l) = color.split_hsl();
+        let save_row = self.render_save_row(cx);
+        let plane = self.render_plane(cx);
+        let gamut_warning = self.render_gamut_warning(cx);
+        // the preview is dropped when its field loses focus without enter
+        let color = match self.preview {
+            Some((field, preview)) if self.field_view(field).read(cx).is_focused(cx) => preview,
+            _ => *self.color.read(cx),
+        };
         let palette = self.palette.read(cx);
+        let vision = *self.vision.read(cx);
         let mut fade_label_color = palette.at_darkness(300);
         fade_label_color.fade_out(0.5);
 
@@ -86,6 +565,7 @@ impl Render for ColorInputView {
             .w(px(500.))
             .flex_center()
             .flex_col()
+            .on_key_down(cx.listener(Self::handle_key_down))
             .child(
                 div()
                     .w_full()
@@ -111,45 +591,44 @@ impl Render for ColorInputView {
                                     .child(
                                         div()
                                             .h_full()
-                                            .border_t_width(px(4.))
-                                            .border_l_width(px(32.))
+                                            .border_l_width(px(24.))
                                             .flex()
                                             .items_center()
                                             .justify_start()
-                                            .child(color.to_rgb().to_string().to_uppercase()),
+                                            .child(div().w(px(120.)).child(self.hex_field.clone())),
                                     )
                                     .child(
                                         div()
                                             .h_full()
-                                            .border_t_width(px(4.))
                                             .border_r_width(px(14.))
                                             .flex()
                                             .flex_row()
                                             .items_center()
                                             .justify_end()
-                                            .child(
-                                                div()
-                                                    .border_r_width(px(4.))
-                                                    .text_color(fade_label_color)
-                                                    .child("H"),
-                                            )
-                                            .child(format!("{:<3}", h))
-                                            .child(
-                                                div()
-                                                    .border_l_width(px(16.))
-                                                    .border_r_width(px(4.))
-                                                    .text_color(fade_label_color)
-                                                    .child("S"),
-                                            )
-                                            .child(format!("{:<3}", s))
-                                            .child(
-                                                div()
-                                                    .border_l_width(px(16.))
-                                                    .border_r_width(px(4.))
-                                                    .text_color(fade_label_color)
-                                                    .child("L"),
-                                            )
-                                            .child(format!("{:<3}", l)),
+                                            .children(
+                                                [
+                                                    ("H", &self.hue_field),
+                                                    ("S", &self.saturation_field),
+                                                    ("L", &self.lightness_field),
+                                                    ("A", &self.alpha_field),
+                                                ]
+                                                .map(|(label, field)| {
+                                                    div()
+                                                        .flex()
+                                                        .flex_row()
+                                                        .items_center()
+                                                        .child(
+                                                            div()
+                                                                .border_l_width(px(10.))
+                                                                .border_r_width(px(4.))
+                                                                .text_color(fade_label_color)
+                                                                .child(label),
+                                                        )
+                                                        .child(
+                                                            div().w(px(44.)).child(field.clone()),
+                                                        )
+                                                }),
+                                            ),
                                     ),
                             ),
                     )
@@ -159,15 +638,41 @@ impl Render for ColorInputView {
                                 .rounded_full()
                                 .w(px(100.))
                                 .h(px(100.))
-                                .bg(color.canonicalize()),
+                                .bg(vision.simulate_hsla(color.canonicalize())),
                         ),
                     ),
             )
-            .child("COLOR")
-            .child(self.hue_slider.clone())
-            .child(div().h(px(20.)))
-            .child(self.saturation_slider.clone())
-            .child(div().h(px(20.)))
-            .child(self.lightness_slider.clone())
+            .when_some(self.error.clone(), |div0, error| {
+                div0.child(
+                    div()
+                        .h(px(20.))
+                        .text_size(px(12.))
+                        .text_color(rgb(0xef4444))
+                        .child(error),
+                )
+            })
+            .children(gamut_warning)
+            .child(save_row)
+            .child(
+                div()
+                    .w_full()
+                    .flex()
+                    .flex_row()
+                    .gap(px(20.))
+                    .child(plane)
+                    .child(
+                        div()
+                            .flex_1()
+                            .flex_col()
+                            .child("COLOR")
+                            .child(self.hue_slider.clone())
+                            .child(div().h(px(20.)))
+                            .child(self.saturation_slider.clone())
+                            .child(div().h(px(20.)))
+                            .child(self.lightness_slider.clone())
+                            .child(div().h(px(20.)))
+                            .child(self.alpha_slider.clone()),
+                    ),
+            )
     }
 }
@@ -0,0 +1,421 @@
+use crate::{colorspace::*, *};
+use gpui::*;
+use gpui_ext::*;
+use image::{Bgra, ImageBuffer};
+use std::{f32::consts::TAU, sync::Arc};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlaneKind {
+    SaturationLightness, // square of the current hue
+    HueWheel,            // hue around, saturation outwards, at the current lightness
+    ChromaLightness,     // OKLCH plane of the current hue, with the sRGB gamut boundary
+}
+
+impl PlaneKind {
+    pub const ALL: [PlaneKind; 3] = [
+        PlaneKind::SaturationLightness,
+        PlaneKind::HueWheel,
+        PlaneKind::ChromaLightness,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PlaneKind::SaturationLightness => "S/L",
+            PlaneKind::HueWheel => "Wheel",
+            PlaneKind::ChromaLightness => "OKLCH",
+        }
+    }
+
+    // OKLCH chroma at the right edge of the chroma/lightness plane
+    const MAX_CHROMA: f32 = 0.37;
+
+    /// What the image depends on besides its size, to know when to generate it again.
+    fn image_key(self, c: Hsla, oklch_hue: f32) -> u32 {
+        match self {
+            PlaneKind::SaturationLightness => (c.h.clamp(0., 1.) * 360.) as u32,
+            PlaneKind::HueWheel => (c.l.clamp(0., 1.) * 100.) as u32,
+            PlaneKind::ChromaLightness => oklch_hue as u32,
+        }
+    }
+
+    /// Position of the color in the plane, from (0, 0) at the top left to (1, 1).
+    fn position(self, c: Hsla) -> (f32, f32) {
+        match self {
+            PlaneKind::SaturationLightness => (c.s, 1. - c.l),
+            PlaneKind::HueWheel => {
+                let (r, angle) = (c.s * 0.5, c.h * TAU);
+                (0.5 + r * angle.cos(), 0.5 - r * angle.sin())
+            }
+            PlaneKind::ChromaLightness => {
+                let [l, c, _] = hsla_to_oklch(c);
+                ((c / Self::MAX_CHROMA).min(1.), 1. - l)
+            }
+        }
+    }
+
+    fn color_at(self, c: Hsla, oklch_hue: f32, x: f32, y: f32) -> Hsla {
+        let (x, y) = (x.clamp(0., 1.), y.clamp(0., 1.));
+        match self {
+            PlaneKind::SaturationLightness => hsla(c.h, x, 1. - y, c.a),
+            PlaneKind::HueWheel => {
+                let (dx, dy) = (x - 0.5, 0.5 - y);
+                let s = (dx.hypot(dy) * 2.).min(1.);
+                let h = (dy.atan2(dx) / TAU).rem_euclid(1.);
+                hsla(h, s, c.l, c.a)
+            }
+            PlaneKind::ChromaLightness => {
+                let lch = [1. - y, x * Self::MAX_CHROMA, oklch_hue];
+                let [r, g, b] = oklch_to_srgb_gamut_mapped(lch);
+                Rgba { r, g, b, a: c.a }.into()
+            }
+        }
+    }
+
+    fn gen_image(
+        self,
+        w: u32,
+        h: u32,
+        color: Hsla,
+        oklch_hue: f32,
+        vision: ColorVision,
+    ) -> ImageData {
+        let pixel = |rgb: Vec3| {
+            let [r, g, b] = vision.simulate_srgb(rgb);
+            Bgra([channel_byte(b), channel_byte(g), channel_byte(r), 255])
+        };
+        let (fw, fh) = (w.max(1) as f32, h.max(1) as f32);
+
+        let buffer: ImageBuffer<Bgra<u8>, Vec<u8>> = match self {
+            PlaneKind::SaturationLightness => ImageBuffer::from_fn(w, h, |x, y| {
+                let Rgba { r, g, b, .. } =
+                    hsla(color.h, x as f32 / fw, 1. - y as f32 / fh, 1.).to_rgb();
+                pixel([r, g, b])
+            }),
+            PlaneKind::HueWheel => ImageBuffer::from_fn(w, h, |x, y| {
+                let (dx, dy) = (x as f32 / fw - 0.5, 0.5 - y as f32 / fh);
+                if dx.hypot(dy) > 0.5 {
+                    return Bgra([0, 0, 0, 0]);
+                }
+                let c = self.color_at(color, oklch_hue, x as f32 / fw, y as f32 / fh);
+                let Rgba { r, g, b, .. } = hsla(c.h, c.s, c.l, 1.).to_rgb();
+                pixel([r, g, b])
+            }),
+            PlaneKind::ChromaLightness => {
+                let max_chroma: Vec<f32> = (0..h)
+                    .map(|y| max_srgb_chroma(1. - y as f32 / fh, oklch_hue, Self::MAX_CHROMA))
+                    .collect();
+                let line = Self::MAX_CHROMA / fw;
+                ImageBuffer::from_fn(w, h, |x, y| {
+                    let (l, c) = (1. - y as f32 / fh, x as f32 / fw * Self::MAX_CHROMA);
+                    let boundary = max_chroma[y as usize];
+                    if (c - boundary).abs() <= line && boundary < Self::MAX_CHROMA {
+                        return Bgra([255, 255, 255, 255]);
+                    }
+                    let rgb = oklch_to_srgb_gamut_mapped([l, c, oklch_hue]);
+                    if c > boundary {
+                        // outside of sRGB, dimmed to the closest color that fits
+                        pixel(rgb.map(|x| x * 0.35))
+                    } else {
+                        pixel(rgb)
+                    }
+                })
+            }
+        };
+        ImageData::new(buffer)
+    }
+}
+
+fn hsla_to_oklch(c: Hsla) -> Vec3 {
+    let Rgba { r, g, b, .. } = c.into();
+    lab_to_lch(linear_srgb_to_oklab(srgb_to_linear_srgb([r, g, b])))
+}
+
+/// 2D picker next to the sliders. Emits the same events as `ColorSlider` on the shared color.
+pub struct ColorPlane {
+    color: Model<Hsla>,
+    vision: Model<ColorVision>,
+    kind: PlaneKind,
+    oklch_hue: f32, // kept while the color is gray, where the OKLCH hue is meaningless
+    image_key: u32,
+    bounds: Option<Bounds<Pixels>>,
+    image_data: Option<Arc<ImageData>>,
+
+    dragging: bool,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ColorPlane {
+    pub fn new<T: 'static>(
+        cx: &mut ViewContext<T>,
+        kind: PlaneKind,
+        color: Model<Hsla>,
+        vision: Model<ColorVision>,
+    ) -> View<Self> {
+        cx.new_view(|cx| {
+            let _subscriptions = vec![
+                cx.observe(&color, Self::handle_color_change),
+                cx.observe(&vision, |this, _, cx| {
+                    this.image_data = None;
+                    cx.notify();
+                }),
+            ];
+            let c = *color.read(cx);
+            let [_, _, oklch_hue] = hsla_to_oklch(c);
+
+            Self {
+                color,
+                vision,
+                kind,
+                oklch_hue,
+                image_key: kind.image_key(c, oklch_hue),
+                bounds: None,
+                image_data: None,
+                dragging: false,
+                focus_handle: cx.focus_handle(),
+                _subscriptions,
+            }
+        })
+    }
+
+    pub fn kind(&self) -> PlaneKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, cx: &mut ViewContext<Self>, kind: PlaneKind) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.image_key = kind.image_key(*self.color.read(cx), self.oklch_hue);
+            self.image_data = None;
+            cx.notify();
+        }
+    }
+
+    fn handle_color_change(&mut self, color: Model<Hsla>, cx: &mut ViewContext<Self>) {
+        let c = *color.read(cx);
+        // dragging in the OKLCH plane keeps its hue even where gamut mapping drifts it
+        if !(self.dragging && self.kind == PlaneKind::ChromaLightness) {
+            let [_, chroma, hue] = hsla_to_oklch(c);
+            if chroma > 0.02 {
+                self.oklch_hue = hue;
+            }
+        }
+        let key = self.kind.image_key(c, self.oklch_hue);
+        if key != self.image_key {
+            self.image_key = key;
+            self.image_data = None;
+        }
+        cx.notify();
+    }
+
+    fn handle_mouse(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        ev: MouseClickOrDrag,
+        pos: Point<Pixels>,
+    ) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+        match ev {
+            MouseClickOrDrag::Click => {
+                if !bounds.contains(&pos) {
+                    return;
+                }
+                self.dragging = true;
+                cx.focus(&self.focus_handle);
+                self.color
+                    .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureStarted));
+            }
+            MouseClickOrDrag::Release => {
+                if self.dragging {
+                    self.dragging = false;
+                    self.color
+                        .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureEnded));
+                }
+                return;
+            }
+            MouseClickOrDrag::Drag => {
+                if !self.dragging {
+                    return;
+                }
+            }
+        }
+
+        let x = (pos.x - bounds.origin.x) / bounds.size.width;
+        let y = (pos.y - bounds.origin.y) / bounds.size.height;
+        let (kind, oklch_hue) = (self.kind, self.oklch_hue);
+        self.color.update(cx, |color, cx| {
+            *color = kind.color_at(*color, oklch_hue, x, y);
+            cx.notify();
+            cx.emit(ColorSliderEvent::ColorChanged(*color));
+        });
+    }
+}
+
+impl FocusableView for ColorPlane {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ColorPlane {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let crosshair_size = px(14.);
+        let focused = self.focus_handle.is_focused(cx);
+        let color = *self.color.read(cx);
+        let (x, y) = self.kind.position(color);
+        let (left, top) = match self.bounds {
+            None => (px(0.), px(0.)),
+            Some(bounds) => (
+                bounds.size.width * x - crosshair_size / 2.,
+                bounds.size.height * y - crosshair_size / 2.,
+            ),
+        };
+
+        div()
+            .id("color-plane")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(ColorPlaneElement::new(cx.view()))
+            .child(
+                div()
+                    .absolute()
+                    .top(top)
+                    .left(left)
+                    .size(crosshair_size)
+                    .rounded_full()
+                    .border_2()
+                    .border_color(if focused {
+                        rgb(0x88ffff)
+                    } else {
+                        rgb(0xffffff)
+                    })
+                    .child(
+                        div()
+                            .size_full()
+                            .rounded_full()
+                            .border_1()
+                            .border_color(rgb(0x000000)),
+                    ),
+            )
+    }
+}
+
+pub struct ColorPlaneElement {
+    plane_view: View<ColorPlane>,
+    interactivity: Interactivity,
+}
+
+impl StatefulInteractiveElement for ColorPlaneElement {}
+
+impl InteractiveElement for ColorPlaneElement {
+    fn interactivity(&mut self) -> &mut Interactivity {
+        &mut self.interactivity
+    }
+}
+
+impl IntoElement for ColorPlaneElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl ColorPlaneElement {
+    pub fn new(plane_view: &View<ColorPlane>) -> Self {
+        Self {
+            plane_view: plane_view.clone(),
+            interactivity: Default::default(),
+        }
+    }
+}
+
+impl Element for ColorPlaneElement {
+    type BeforeLayout = ();
+    type AfterLayout = Option<Arc<ImageData>>;
+
+    fn before_layout(&mut self, cx: &mut ElementContext) -> (LayoutId, Self::BeforeLayout) {
+        let layout_id = self.interactivity.before_layout(cx, |mut style, cx| {
+            style.size.width = relative(1.).into();
+            style.size.height = relative(1.).into();
+            cx.request_layout(&style, None)
+        });
+        (layout_id, ())
+    }
+
+    fn after_layout(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        cx: &mut ElementContext,
+    ) -> Self::AfterLayout {
+        self.plane_view.update(cx, |view, cx| {
+            let dirty = match view.bounds {
+                None => true,
+                Some(b) => b.size != bounds.size,
+            };
+            if dirty || view.image_data.is_none() {
+                let sf = cx.scale_factor();
+                let Size { width, height } = bounds.size;
+                view.image_data = Some(Arc::new(view.kind.gen_image(
+                    (width * sf).into(),
+                    (height * sf).into(),
+                    view.color.read(cx).to_owned(),
+                    view.oklch_hue,
+                    view.vision.read(cx).to_owned(),
+                )));
+            }
+            view.bounds = Some(bounds);
+            view.image_data.clone()
+        })
+    }
+
+    fn paint(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        after_layout: &mut Self::AfterLayout,
+        cx: &mut ElementContext,
+    ) {
+        let Some(data) = after_layout else {
+            return;
+        };
+        cx.on_mouse_event({
+            let view = self.plane_view.clone();
+            move |ev: &MouseDownEvent, phase, cx| {
+                if ev.button == MouseButton::Left {
+                    view.update(cx, |view, cx| {
+                        view.handle_mouse(cx, MouseClickOrDrag::Click, ev.position)
+                    });
+                }
+            }
+        });
+        cx.on_mouse_event({
+            let view = self.plane_view.clone();
+            move |ev: &MouseMoveEvent, phase, cx| {
+                let state = if let Some(MouseButton::Left) = ev.pressed_button {
+                    MouseClickOrDrag::Drag
+                } else {
+                    MouseClickOrDrag::Release
+                };
+                if view.read(cx).dragging {
+                    view.update(cx, |view, cx| view.handle_mouse(cx, state, ev.position));
+                }
+            }
+        });
+        cx.on_mouse_event({
+            let view = self.plane_view.clone();
+            move |ev: &MouseUpEvent, phase, cx| {
+                if ev.button == MouseButton::Left && view.read(cx).dragging {
+                    view.update(cx, |view, cx| {
+                        view.handle_mouse(cx, MouseClickOrDrag::Release, ev.position)
+                    });
+                }
+            }
+        });
+        cx.paint_image(bounds, Corners::all(px(4.)), data.clone(), false)
+            .log_err();
+    }
+}
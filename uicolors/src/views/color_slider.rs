@@ -3,10 +3,14 @@ use gpui::*;
 use gpui_ext::*;
 use image::{Bgra, ImageBuffer};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ColorSliderEvent {
     ColorChanged(Hsla),
+    // a drag from mouse down to mouse up, to record it as a single change
+    GestureStarted,
+    GestureEnded,
 }
 
 impl EventEmitter<ColorSliderEvent> for Hsla {}
@@ -16,10 +20,11 @@ pub enum ColorScale {
     Hue,
     Saturation,
     Lightness,
+    Alpha,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum MouseClickOrDrag {
+pub enum MouseClickOrDrag {
     Click,
     Drag,
     Release,
@@ -31,6 +36,7 @@ impl ColorScale {
             ColorScale::Hue => (0., 360.),
             ColorScale::Saturation => (0., 100.),
             ColorScale::Lightness => (0., 100.),
+            ColorScale::Alpha => (0., 100.),
         }
     }
 
@@ -39,6 +45,30 @@ impl ColorScale {
             ColorScale::Hue => (c.h.clamp(0., 1.) * 360.) as u16,
             ColorScale::Saturation => (c.s.clamp(0., 1.) * 100.) as u16,
             ColorScale::Lightness => (c.l.clamp(0., 1.) * 100.) as u16,
+            ColorScale::Alpha => (c.a.clamp(0., 1.) * 100.) as u16,
+        }
+    }
+
+    /// The channel in the units of `range`, e.g. degrees for hue.
+    fn value(&self, c: Hsla) -> f32 {
+        let (_, max) = self.range();
+        let x = match self {
+            ColorScale::Hue => c.h,
+            ColorScale::Saturation => c.s,
+            ColorScale::Lightness => c.l,
+            ColorScale::Alpha => c.a,
+        };
+        x * max
+    }
+
+    fn with_value(&self, c: Hsla, value: f32) -> Hsla {
+        let (min, max) = self.range();
+        let x = value.clamp(min, max) / max;
+        match self {
+            ColorScale::Hue => Hsla { h: x, ..c },
+            ColorScale::Saturation => Hsla { s: x, ..c },
+            ColorScale::Lightness => Hsla { l: x, ..c },
+            ColorScale::Alpha => Hsla { a: x, ..c },
         }
     }
 
@@ -53,14 +83,22 @@ impl ColorScale {
                 Self::Hue.unit(c0) != Self::Hue.unit(c1)
                     || Self::Lightness.unit(c0) != Self::Lightness.unit(c1)
             }
+            ColorScale::Alpha => {
+                Self::Hue.unit(c0) != Self::Hue.unit(c1)
+                    || Self::Saturation.unit(c0) != Self::Saturation.unit(c1)
+                    || Self::Lightness.unit(c0) != Self::Lightness.unit(c1)
+            }
         }
     }
 
-    fn gen_image(&self, w: u32, h: u32, padding: u32, color: Hsla) -> ImageData {
-        fn cv(f: f32) -> u8 {
-            (f * 256.).clamp(0., 255.) as u8
-        }
-
+    fn gen_image(
+        &self,
+        w: u32,
+        h: u32,
+        padding: u32,
+        color: Hsla,
+        vision: ColorVision,
+    ) -> ImageData {
         let rw = w - padding * 2;
         let (x0, x1) = (padding, (w - padding));
         let buffer: ImageBuffer<Bgra<u8>, Vec<u8>> = match self {
@@ -72,7 +110,8 @@ impl ColorScale {
                 };
                 let color = hsla(hue, 1., 0.5, 1.);
                 let Rgba { r, g, b, a: _ } = color.to_rgb();
-                Bgra([cv(b), cv(g), cv(r), 255])
+                let [r, g, b] = vision.simulate_srgb([r, g, b]);
+                Bgra([channel_byte(b), channel_byte(g), channel_byte(r), 255])
             }),
             ColorScale::Saturation => ImageBuffer::from_fn(w, h, |x, _y| {
                 let saturation = if x <= x0 {
@@ -86,7 +125,8 @@ impl ColorScale {
                 // let saturation = 0.;
                 let color = hsla(color.h, saturation, color.l, 1.);
                 let Rgba { r, g, b, a: _ } = color.to_rgb();
-                Bgra([cv(b), cv(g), cv(r), 255])
+                let [r, g, b] = vision.simulate_srgb([r, g, b]);
+                Bgra([channel_byte(b), channel_byte(g), channel_byte(r), 255])
             }),
             ColorScale::Lightness => ImageBuffer::from_fn(w, h, |x, _y| {
                 let lightness = if x <= x0 {
@@ -100,7 +140,27 @@ impl ColorScale {
                 // let lightness = 0.;
                 let color = hsla(color.h, color.s, lightness, 1.);
                 let Rgba { r, g, b, a: _ } = color.to_rgb();
-                Bgra([cv(b), cv(g), cv(r), 255])
+                let [r, g, b] = vision.simulate_srgb([r, g, b]);
+                Bgra([channel_byte(b), channel_byte(g), channel_byte(r), 255])
+            }),
+            ColorScale::Alpha => ImageBuffer::from_fn(w, h, |x, y| {
+                let alpha = if x <= x0 {
+                    0.
+                } else if x >= x1 {
+                    1.
+                } else {
+                    (x - x0) as f32 / rw as f32
+                };
+                let bg = checkerboard(x, y, (h / 2).max(1));
+                let Rgba { r, g, b, a: _ } = hsla(color.h, color.s, color.l, 1.).to_rgb();
+                let [r, g, b] = vision.simulate_srgb([r, g, b]);
+                let blend = |c: f32| c * alpha + bg * (1. - alpha);
+                Bgra([
+                    channel_byte(blend(b)),
+                    channel_byte(blend(g)),
+                    channel_byte(blend(r)),
+                    255,
+                ])
             }),
         };
         ImageData::new(buffer)
@@ -109,6 +169,7 @@ impl ColorScale {
 
 pub struct ColorSlider {
     color: Model<Hsla>,
+    vision: Model<ColorVision>,
     prev_color: Hsla,
     scale: ColorScale,
     padding: Pixels,
@@ -117,6 +178,9 @@ pub struct ColorSlider {
     image_data: Option<Arc<ImageData>>,
 
     dragging: bool,
+    key_held: bool,        // an arrow key is down, its repeats are a single change
+    scroll_remainder: f32, // scrolled distance not yet applied, in units
+    scroll_gesture: Option<Task<()>>, // ends the scroll gesture once scrolling stops
     focus_handle: FocusHandle,
     _subscriptions: Vec<Subscription>,
 }
@@ -126,12 +190,17 @@ impl ColorSlider {
         cx: &mut ViewContext<T>,
         scale: ColorScale,
         color: Model<Hsla>,
+        vision: Model<ColorVision>,
     ) -> View<Self> {
         cx.new_view(|cx| {
-            let _subscriptions = vec![cx.observe(&color, Self::handle_color_change)];
+            let _subscriptions = vec![
+                cx.observe(&color, Self::handle_color_change),
+                cx.observe(&vision, Self::handle_vision_change),
+            ];
 
             Self {
                 color: color.clone(),
+                vision,
                 prev_color: color.read(cx).to_owned(),
                 scale,
                 padding: px(4.),
@@ -139,6 +208,9 @@ impl ColorSlider {
                 bounds: None,
                 image_data: None,
                 dragging: false,
+                key_held: false,
+                scroll_remainder: 0.,
+                scroll_gesture: None,
                 focus_handle: cx.focus_handle(),
                 _subscriptions,
             }
@@ -153,6 +225,11 @@ impl ColorSlider {
         self.prev_color = c;
     }
 
+    fn handle_vision_change(&mut self, _: Model<ColorVision>, cx: &mut ViewContext<Self>) {
+        self.image_data = None;
+        cx.notify();
+    }
+
     fn handle_mouse_click_or_drag(
         &mut self,
         cx: &mut ViewContext<Self>,
@@ -174,9 +251,16 @@ impl ColorSlider {
                         return;
                     }
                     self.dragging = true;
+                    cx.focus(&self.focus_handle);
+                    self.color
+                        .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureStarted));
                 }
                 MouseClickOrDrag::Release => {
-                    self.dragging = false;
+                    if self.dragging {
+                        self.dragging = false;
+                        self.color
+                            .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureEnded));
+                    }
                     return;
                 }
                 MouseClickOrDrag::Drag => {
@@ -213,11 +297,116 @@ impl ColorSlider {
                         cx.emit(ColorSliderEvent::ColorChanged(*color));
                     });
                 }
+                ColorScale::Alpha => {
+                    let alpha = (x / w).clamp(0., 1.);
+                    self.color.update(cx, |color, cx| {
+                        color.a = alpha;
+                        cx.notify();
+                        cx.emit(ColorSliderEvent::ColorChanged(*color));
+                    });
+                }
             }
         }
     }
 }
 
+impl ColorSlider {
+    // scrolled pixels per unit
+    const SCROLL_STEP: f32 = 20.;
+    // a pause in scrolling that ends a scroll gesture
+    const SCROLL_IDLE: Duration = Duration::from_millis(300);
+
+    /// Returns whether the color changed.
+    fn set_value(&mut self, cx: &mut ViewContext<Self>, value: f32) -> bool {
+        let scale = self.scale;
+        self.color.update(cx, |color, cx| {
+            let new_color = scale.with_value(*color, value);
+            if new_color == *color {
+                return false;
+            }
+            *color = new_color;
+            cx.notify();
+            cx.emit(ColorSliderEvent::ColorChanged(*color));
+            true
+        })
+    }
+
+    /// Arrows nudge by one unit or by ten with shift, Home and End jump to the ends.
+    fn handle_key_down(&mut self, ev: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let Keystroke { key, modifiers, .. } = &ev.keystroke;
+        if modifiers.command || modifiers.control || modifiers.alt {
+            return;
+        }
+        let step = if modifiers.shift { 10. } else { 1. };
+        let value = self.scale.value(*self.color.read(cx)).round();
+        let (min, max) = self.scale.range();
+        let value = match key.as_str() {
+            "left" | "down" => value - step,
+            "right" | "up" => value + step,
+            "home" => min,
+            "end" => max,
+            _ => return,
+        };
+        cx.stop_propagation();
+        if !self.key_held {
+            self.key_held = true;
+            self.color
+                .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureStarted));
+        }
+        self.set_value(cx, value);
+    }
+
+    fn handle_key_up(&mut self, _: &KeyUpEvent, cx: &mut ViewContext<Self>) {
+        if self.key_held {
+            self.key_held = false;
+            self.color
+                .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureEnded));
+        }
+    }
+
+    fn handle_scroll_wheel(&mut self, ev: &ScrollWheelEvent, cx: &mut ViewContext<Self>) {
+        let delta = ev.delta.pixel_delta(px(Self::SCROLL_STEP));
+        // vertical wheels and horizontal trackpad swipes both move the thumb
+        let delta = if delta.y.abs() > delta.x.abs() {
+            delta.y
+        } else {
+            delta.x
+        };
+        let step = if ev.modifiers.shift { 10. } else { 1. };
+        self.scroll_remainder += f32::from(delta) / Self::SCROLL_STEP * step;
+        let units = self.scroll_remainder.trunc();
+        if units == 0. {
+            return;
+        }
+        self.scroll_remainder -= units;
+        let value = self.scale.value(*self.color.read(cx)).round();
+        if self.scroll_gesture.is_none() {
+            self.color
+                .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureStarted));
+        }
+        // at either end of the scale the scroll is left to the parent
+        if self.set_value(cx, value + units) {
+            cx.stop_propagation();
+        }
+        // a whole swipe is a single change, replacing the task pushes its end back
+        self.scroll_gesture = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(Self::SCROLL_IDLE).await;
+            if let Some(this) = this.upgrade() {
+                this.update(&mut cx, |this, cx| this.end_scroll_gesture(cx))
+                    .log_err();
+            }
+        }));
+    }
+
+    fn end_scroll_gesture(&mut self, cx: &mut ViewContext<Self>) {
+        if self.scroll_gesture.take().is_some() {
+            self.scroll_remainder = 0.;
+            self.color
+                .update(cx, |_, cx| cx.emit(ColorSliderEvent::GestureEnded));
+        }
+    }
+}
+
 impl FocusableView for ColorSlider {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
         self.focus_handle.clone()
@@ -230,16 +419,20 @@ impl Render for ColorSlider {
         let border = px(3.);
         let focused = self.focus_handle.is_focused(cx);
         let color = self.color.read(cx);
+        let vision = *self.vision.read(cx);
         let value = match self.scale {
             ColorScale::Hue => color.h,
             ColorScale::Saturation => color.s,
             ColorScale::Lightness => color.l,
+            ColorScale::Alpha => color.a,
         };
         let thumb_color = match self.scale {
             ColorScale::Hue => hsla(color.h, 1., 0.5, 1.),
             ColorScale::Saturation => hsla(color.h, color.s, color.l, 1.),
             ColorScale::Lightness => hsla(color.h, color.s, color.l, 1.),
+            ColorScale::Alpha => hsla(color.h, color.s, color.l, color.a),
         };
+        let thumb_color = vision.simulate_hsla(thumb_color);
         let thumb_x: Pixels = match self.bounds {
             None => px(0.),
             Some(bounds) => {
@@ -249,6 +442,11 @@ impl Render for ColorSlider {
         };
 
         div()
+            .id("color-slider")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .on_key_up(cx.listener(Self::handle_key_up))
+            .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
             .w_full()
             .h(thumb_size)
             .flex_center()
@@ -266,7 +464,12 @@ impl Render for ColorSlider {
                     .w(thumb_size)
                     .h(thumb_size)
                     .rounded(thumb_size / 2.)
-                    .bg(hsla(0., 0., 0.8, 1.))
+                    // the ring of the thumb shows which slider the keys go to
+                    .bg(if focused {
+                        rgb(0x88ffff).into()
+                    } else {
+                        hsla(0., 0., 0.8, 1.)
+                    })
                     .child(
                         div()
                             .absolute()
@@ -347,6 +550,7 @@ impl Element for ColorSliderElement {
                         (height * sf).into(),
                         (view.padding * sf).into(),
                         view.color.read(cx).to_owned(),
+                        view.vision.read(cx).to_owned(),
                     )
                 }))
             } else {
@@ -395,6 +599,20 @@ impl Element for ColorSliderElement {
                     };
                 }
             });
+            cx.on_mouse_event({
+                let view = self.slider_view.clone();
+                move |ev: &MouseUpEvent, phase, cx| {
+                    if ev.button == MouseButton::Left && view.read(cx).dragging {
+                        view.update(cx, |view, cx| {
+                            view.handle_mouse_click_or_drag(
+                                cx,
+                                MouseClickOrDrag::Release,
+                                ev.position,
+                            )
+                        });
+                    }
+                }
+            });
             cx.paint_image(bounds, Corners::all(px(0.)), data.clone(), false)
                 .log_err();
         }
@@ -0,0 +1,286 @@
+use super::*;
+use crate::{
+    label_color, nearest_named_color, AppState, ColorFormat, DeltaE, NamedColor, ResultExt,
+};
+use gpui::*;
+use gpui_ext::*;
+use std::sync::Arc;
+
+/// The current color next to another one, with how different they look.
+pub struct CompareView {
+    workspace: WeakView<Workspace>,
+    app_state: Arc<AppState>,
+    other: ColorFormat,
+    other_field: View<TextField>,
+    error: Option<String>, // why the text being typed is not a color
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CompareView {
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
+        app_state: Arc<AppState>,
+    ) -> Self {
+        // start with the Tailwind color closest to the current one
+        let color = *app_state.color.read(cx);
+        let other = nearest_named_color(color, None, DeltaE::Ciede2000)
+            .map_or(ColorFormat::Rgb(255, 255, 255), |x| x.color);
+        let other_field = cx.new_view(|cx| {
+            TextField::new(
+                cx,
+                other.to_rgb().to_string().to_uppercase(),
+                "Color to compare",
+            )
+        });
+        let _subscriptions = vec![
+            cx.observe(&app_state.color, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.library, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.color_vision, |_, _, cx| cx.notify()),
+            cx.subscribe(&other_field, Self::handle_field_event),
+        ];
+        Self {
+            workspace,
+            app_state,
+            other,
+            other_field,
+            error: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
+
+    fn handle_field_event(
+        &mut self,
+        field: View<TextField>,
+        event: &TextFieldEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let text = match event {
+            TextFieldEvent::Changed(text) | TextFieldEvent::Submit(text) => text,
+            TextFieldEvent::Cancel => {
+                let other = self.other;
+                self.set_other(cx, other);
+                return;
+            }
+        };
+        let result = ColorFormat::parse(text.trim());
+        field.update(cx, |field, cx| field.set_invalid(cx, result.is_err()));
+        match result {
+            Ok(color) => {
+                self.other = color;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn set_other(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat) {
+        self.other = color;
+        self.error = None;
+        self.other_field.update(cx, |field, cx| {
+            field.set_text(cx, color.to_rgb().to_string().to_uppercase());
+            field.set_invalid(cx, false);
+        });
+        cx.notify();
+    }
+
+    fn set_color(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat) {
+        self.workspace
+            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+            .log_err();
+    }
+
+    fn swap(&mut self, cx: &mut ViewContext<Self>) {
+        let color = *self.app_state.color.read(cx);
+        let other = self.other;
+        self.set_other(cx, color);
+        self.set_color(cx, other);
+    }
+
+    // the named color closest to the current one, other than the current color itself
+    fn nearest_other(&self, cx: &AppContext) -> Option<NamedColor> {
+        let color = *self.app_state.color.read(cx);
+        let library = self.app_state.library.read(cx).as_ref();
+        nearest_named_color(color, library, DeltaE::Ciede2000)
+            .filter(|x| x.distance > 0.01)
+            .or_else(|| {
+                // the color has a name already: look for the next one
+                let library = library.map(|library| {
+                    let mut library = library.clone();
+                    library.items.retain(|x| x.color.to_rgb() != color.to_rgb());
+                    library
+                });
+                let found = nearest_named_color(color, library.as_ref(), DeltaE::Ciede2000)?;
+                (found.distance > 0.01).then_some(found)
+            })
+    }
+
+    fn name_of(&self, cx: &AppContext, color: ColorFormat) -> Option<String> {
+        let library = self.app_state.library.read(cx).as_ref();
+        nearest_named_color(color, library, DeltaE::Ciede2000)
+            .filter(|x| x.distance < 0.01)
+            .map(|x| x.name)
+    }
+
+    fn render_swatch(
+        &self,
+        cx: &mut ViewContext<Self>,
+        label: &'static str,
+        color: ColorFormat,
+    ) -> Div {
+        let vision = *self.app_state.color_vision.read(cx);
+        let bg = vision.simulate_hsla(color.canonicalize());
+        let name = self.name_of(cx, color);
+        div()
+            .flex_1()
+            .h(px(200.))
+            .p(px(16.))
+            .bg(bg)
+            .text_color(label_color(bg))
+            .flex()
+            .flex_col()
+            .justify_between()
+            .child(div().text_size(px(13.)).child(label))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .child(
+                        div()
+                            .font("Monaspace Xenon")
+                            .text_size(px(16.))
+                            .child(color.to_rgb().to_string().to_uppercase()),
+                    )
+                    .children(name.map(|name| div().text_size(px(13.)).child(name))),
+            )
+    }
+
+    fn render_metrics(&self, cx: &mut ViewContext<Self>, color: ColorFormat) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let (l0, c0, h0) = color.split_oklch();
+        let (l1, c1, h1) = self.other.split_oklch();
+        let dh = (h1 - h0 + 540.).rem_euclid(360.) - 180.;
+        let row = |name: String, value: String, note: String| {
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .h(px(28.))
+                .child(
+                    div()
+                        .w(px(100.))
+                        .text_color(palette.at_darkness(400))
+                        .child(name),
+                )
+                .child(
+                    div()
+                        .w(px(100.))
+                        .font("Monaspace Xenon")
+                        .text_color(palette.at_darkness(200))
+                        .child(value),
+                )
+                .child(div().text_color(palette.at_darkness(500)).child(note))
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .text_size(px(14.))
+            .children(DeltaE::ALL.into_iter().map(|metric| {
+                let d = metric.between(color, self.other);
+                let jnd = metric.just_noticeable();
+                let note = if d < jnd {
+                    "not noticeable"
+                } else if d < jnd * 5. {
+                    "noticeable side by side"
+                } else {
+                    "clearly different"
+                };
+                let value = match metric {
+                    DeltaE::Oklab => format!("{:.4}", d),
+                    _ => format!("{:.2}", d),
+                };
+                row(metric.name().to_owned(), value, note.to_owned())
+            }))
+            .child(row(
+                "OKLCH".to_owned(),
+                format!("ΔL {:+.3}", l1 - l0),
+                format!("ΔC {:+.3}  ΔH {:+.1}°", c1 - c0, dh),
+            ))
+    }
+}
+
+impl FocusableView for CompareView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for CompareView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let palette = *self.app_state.palette.read(cx);
+        let color = *self.app_state.color.read(cx);
+        let other = self.other;
+        let nearest = self.nearest_other(cx);
+        let swatches = div()
+            .w_full()
+            .flex()
+            .flex_row()
+            .rounded(px(8.))
+            .overflow_hidden()
+            .child(self.render_swatch(cx, "Current color", color))
+            .child(self.render_swatch(cx, "Compared with", other));
+        let metrics = self.render_metrics(cx, color);
+
+        div()
+            .id("compare")
+            .size_full()
+            .overflow_y_scroll()
+            .bg(rgb(0x000000))
+            .border_size(rems(2.), rems(2.), rems(2.), rems(2.))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .text_size(rems(1.5))
+                    .text_color(palette.at_darkness(300))
+                    .child("Compare"),
+            )
+            .child(swatches)
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(px(8.))
+                    .child(div().w(px(200.)).child(self.other_field.clone()))
+                    .child(
+                        option_button("swap-colors", "Swap", false, &palette)
+                            .on_click(cx.listener(|this, _, cx| this.swap(cx))),
+                    )
+                    .child(
+                        option_button("use-compared", "Use", false, &palette)
+                            .on_click(cx.listener(move |this, _, cx| this.set_color(cx, other))),
+                    )
+                    .children(nearest.map(|nearest| {
+                        let label = format!("Nearest: {}", nearest.name);
+                        option_button("compare-nearest", label, false, &palette).on_click(
+                            cx.listener(move |this, _, cx| this.set_other(cx, nearest.color)),
+                        )
+                    })),
+            )
+            .when_some(self.error.clone(), |div0, error| {
+                div0.child(
+                    div()
+                        .text_size(px(12.))
+                        .text_color(rgb(0xef4444))
+                        .child(error),
+                )
+            })
+            .child(metrics)
+    }
+}
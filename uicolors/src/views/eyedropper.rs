@@ -0,0 +1,477 @@
+use super::*;
+use crate::{
+    clipboard_image, dominant_colors, label_color, load_image, pixel_color, AppState, ColorFormat,
+    ColorItem, ColorLibrary, ResultExt,
+};
+use anyhow::Result;
+use gpui::*;
+use gpui_ext::*;
+use image::{Bgra, ImageBuffer, RgbaImage};
+use std::{path::PathBuf, sync::Arc};
+
+struct Source {
+    name: String,
+    image: Arc<RgbaImage>,
+    image_data: Arc<ImageData>,
+    dominant: Vec<(ColorFormat, f32)>, // color, share of the pixels
+}
+
+/// Picks colors out of an image opened from a file or pasted from the clipboard.
+pub struct Eyedropper {
+    workspace: WeakView<Workspace>,
+    app_state: Arc<AppState>,
+    source: Option<Source>,
+    image_bounds: Option<Bounds<Pixels>>, // where the image is drawn
+    hovered: Option<(u32, u32)>,
+    loupe: Option<Arc<ImageData>>,
+    message: Option<SharedString>,
+    focus_handle: FocusHandle,
+}
+
+impl Eyedropper {
+    const DOMINANT_COUNT: usize = 8;
+    const LOUPE_RADIUS: i64 = 5; // pixels shown around the hovered one
+    const LOUPE_ZOOM: u32 = 12;
+    const MAX_ZOOM: f32 = 8.;
+
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
+        app_state: Arc<AppState>,
+    ) -> Self {
+        Self {
+            workspace,
+            app_state,
+            source: None,
+            image_bounds: None,
+            hovered: None,
+            loupe: None,
+            message: None,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn open_image(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Some(paths)) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let name = path
+                .file_stem()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let source = cx
+                .background_executor()
+                .spawn(async move { Self::load_source(name, || load_image(&path)) })
+                .await;
+            if let Some(this) = this.upgrade() {
+                this.update(&mut cx, |this, cx| this.set_source(cx, source))
+                    .log_err();
+            }
+        })
+        .detach();
+    }
+
+    fn paste_image(&mut self, cx: &mut ViewContext<Self>) {
+        cx.spawn(|this, mut cx| async move {
+            let source = cx
+                .background_executor()
+                .spawn(async move { Self::load_source("Pasted".to_owned(), clipboard_image) })
+                .await;
+            if let Some(this) = this.upgrade() {
+                this.update(&mut cx, |this, cx| this.set_source(cx, source))
+                    .log_err();
+            }
+        })
+        .detach();
+    }
+
+    // decodes and analyses the image, off the main thread
+    fn load_source(name: String, load: impl FnOnce() -> Result<RgbaImage>) -> Result<Source> {
+        let image = load()?;
+        let bgra: ImageBuffer<Bgra<u8>, Vec<u8>> =
+            ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+                let [r, g, b, a] = image.get_pixel(x, y).0;
+                Bgra([b, g, r, a])
+            });
+        Ok(Source {
+            name,
+            dominant: dominant_colors(&image, Self::DOMINANT_COUNT),
+            image: Arc::new(image),
+            image_data: Arc::new(ImageData::new(bgra)),
+        })
+    }
+
+    fn set_source(&mut self, cx: &mut ViewContext<Self>, source: Result<Source>) {
+        match source {
+            Ok(source) => {
+                self.source = Some(source);
+                self.message = None;
+            }
+            Err(err) => self.message = Some(format!("{:#}", err).into()),
+        }
+        self.image_bounds = None;
+        self.hovered = None;
+        self.loupe = None;
+        cx.notify();
+    }
+
+    /// The image pixel under the mouse.
+    fn pixel_at(&self, pos: Point<Pixels>) -> Option<(u32, u32)> {
+        let (image, bounds) = (&self.source.as_ref()?.image, self.image_bounds?);
+        if !bounds.contains(&pos) {
+            return None;
+        }
+        let x = (pos.x - bounds.origin.x) / bounds.size.width * image.width() as f32;
+        let y = (pos.y - bounds.origin.y) / bounds.size.height * image.height() as f32;
+        let (x, y) = (x.floor() as u32, y.floor() as u32);
+        (x < image.width() && y < image.height()).then_some((x, y))
+    }
+
+    fn hover(&mut self, cx: &mut ViewContext<Self>, pos: Point<Pixels>) {
+        let hovered = self.pixel_at(pos);
+        if hovered == self.hovered {
+            return;
+        }
+        self.hovered = hovered;
+        self.loupe = match (hovered, &self.source) {
+            (Some((x, y)), Some(source)) => Some(Arc::new(Self::gen_loupe(&source.image, x, y))),
+            _ => None,
+        };
+        cx.notify();
+    }
+
+    fn pick(&mut self, cx: &mut ViewContext<Self>, pos: Point<Pixels>) {
+        let Some(source) = &self.source else {
+            return;
+        };
+        let Some(color) = self
+            .pixel_at(pos)
+            .and_then(|(x, y)| pixel_color(&source.image, x, y))
+        else {
+            return;
+        };
+        self.set_color(cx, color);
+    }
+
+    fn set_color(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat) {
+        self.workspace
+            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+            .log_err();
+        self.message = Some(format!("Picked {}", color.to_rgb().to_string().to_uppercase()).into());
+        cx.notify();
+    }
+
+    fn add_to_library(&mut self, cx: &mut ViewContext<Self>, indices: Vec<usize>) {
+        let Some(source) = &self.source else {
+            return;
+        };
+        let items: Vec<ColorItem> = indices
+            .into_iter()
+            .filter_map(|i| {
+                let (color, _) = source.dominant.get(i)?;
+                let name = format!("{} {}", source.name, i + 1);
+                Some(ColorItem::new(*color, Some(name)))
+            })
+            .collect();
+        let added = self.app_state.library.update(cx, |library, cx| {
+            cx.notify();
+            library
+                .get_or_insert_with(ColorLibrary::default)
+                .import(items)
+        });
+        self.message = Some(format!("Added {} colors to the library", added).into());
+        cx.notify();
+    }
+
+    /// The pixels around (x, y) enlarged, with the center one outlined.
+    fn gen_loupe(image: &RgbaImage, x: u32, y: u32) -> ImageData {
+        let (radius, zoom) = (Self::LOUPE_RADIUS, Self::LOUPE_ZOOM);
+        let size = (radius as u32 * 2 + 1) * zoom;
+        let buffer: ImageBuffer<Bgra<u8>, Vec<u8>> = ImageBuffer::from_fn(size, size, |px, py| {
+            let (cell_x, cell_y) = (px / zoom, py / zoom);
+            let center = cell_x == radius as u32 && cell_y == radius as u32;
+            let edge = |v: u32| v % zoom == 0 || v % zoom == zoom - 1;
+            if center && (edge(px) || edge(py)) {
+                return Bgra([255, 255, 255, 255]);
+            }
+            let sx = x as i64 + cell_x as i64 - radius;
+            let sy = y as i64 + cell_y as i64 - radius;
+            if sx < 0 || sy < 0 || sx >= image.width() as i64 || sy >= image.height() as i64 {
+                return Bgra([24, 24, 24, 255]);
+            }
+            let [r, g, b, _] = image.get_pixel(sx as u32, sy as u32).0;
+            Bgra([b, g, r, 255])
+        });
+        ImageData::new(buffer)
+    }
+
+    /// Largest rectangle with the aspect of the image that fits in `bounds`, centered.
+    fn fit(bounds: Bounds<Pixels>, (w, h): (u32, u32)) -> Bounds<Pixels> {
+        let (w, h) = (px(w.max(1) as f32), px(h.max(1) as f32));
+        let scale = (bounds.size.width / w)
+            .min(bounds.size.height / h)
+            .min(Self::MAX_ZOOM);
+        let fitted = size(w * scale, h * scale);
+        Bounds {
+            origin: point(
+                bounds.origin.x + (bounds.size.width - fitted.width) / 2.,
+                bounds.origin.y + (bounds.size.height - fitted.height) / 2.,
+            ),
+            size: fitted,
+        }
+    }
+
+    fn render_side_panel(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let hovered = match (&self.source, self.hovered) {
+            (Some(source), Some((x, y))) => pixel_color(&source.image, x, y),
+            _ => None,
+        };
+        let dominant = self
+            .source
+            .as_ref()
+            .map(|x| x.dominant.clone())
+            .unwrap_or_default();
+        let label = |text: &'static str| {
+            div()
+                .text_size(px(12.))
+                .text_color(palette.at_darkness(500))
+                .child(text)
+        };
+        let loupe_size = px(((Self::LOUPE_RADIUS as u32 * 2 + 1) * Self::LOUPE_ZOOM) as f32);
+
+        div()
+            .w(px(220.))
+            .flex()
+            .flex_col()
+            .gap(px(8.))
+            .child(
+                div()
+                    .size(loupe_size)
+                    .rounded(px(4.))
+                    .bg(rgb(0x181818))
+                    .when_some(self.loupe.clone(), |div0, loupe| {
+                        div0.child(img(loupe).size(loupe_size))
+                    }),
+            )
+            .child(
+                div()
+                    .h(px(20.))
+                    .font("Monaspace Xenon")
+                    .text_size(px(13.))
+                    .text_color(palette.at_darkness(300))
+                    .children(hovered.map(|x| x.to_rgb().to_string().to_uppercase())),
+            )
+            .when(!dominant.is_empty(), |div0| {
+                let count = dominant.len();
+                div0.child(label("Dominant colors"))
+                    .children(dominant.into_iter().enumerate().map(|(i, (color, share))| {
+                        let bg = color.canonicalize();
+                        div()
+                            .id(("dominant-color", i))
+                            .h(px(28.))
+                            .px(px(8.))
+                            .rounded(px(4.))
+                            .bg(bg)
+                            .text_color(label_color(bg))
+                            .text_size(px(12.))
+                            .cursor_pointer()
+                            .flex()
+                            .flex_row()
+                            .items_center()
+                            .justify_between()
+                            .on_click(cx.listener(move |this, _, cx| this.set_color(cx, color)))
+                            .child(format!(
+                                "{}  {:.0}%",
+                                color.to_rgb().to_string().to_uppercase(),
+                                share * 100.
+                            ))
+                            .child(
+                                div()
+                                    .id(("add-dominant-color", i))
+                                    .px(px(4.))
+                                    .child("+")
+                                    .on_click(cx.listener(move |this, _, cx| {
+                                        cx.stop_propagation();
+                                        this.add_to_library(cx, vec![i]);
+                                    })),
+                            )
+                    }))
+                    .child(
+                        option_button("add-all-dominant", "Add all to library", false, &palette)
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.add_to_library(cx, (0..count).collect())
+                            })),
+                    )
+            })
+    }
+}
+
+impl FocusableView for Eyedropper {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Eyedropper {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let palette = *self.app_state.palette.read(cx);
+        let side_panel = self.render_side_panel(cx);
+
+        div()
+            .size_full()
+            .bg(rgb(0x000000))
+            .border_size(rems(2.), rems(2.), rems(2.), rems(2.))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_size(rems(1.5))
+                            .text_color(palette.at_darkness(300))
+                            .child("Eyedropper"),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .gap(px(8.))
+                            .child(
+                                option_button("open-image", "Open image…", false, &palette)
+                                    .on_click(cx.listener(|this, _, cx| this.open_image(cx))),
+                            )
+                            .child(
+                                option_button("paste-image", "Paste image", false, &palette)
+                                    .on_click(cx.listener(|this, _, cx| this.paste_image(cx))),
+                            ),
+                    ),
+            )
+            .when_some(self.message.clone(), |div0, message| {
+                div0.child(
+                    div()
+                        .text_size(px(13.))
+                        .text_color(palette.at_darkness(300))
+                        .child(message),
+                )
+            })
+            .child(
+                div()
+                    .size_full()
+                    .flex()
+                    .flex_row()
+                    .gap(rems(1.))
+                    .child(div().flex_1().h_full().map(|div0| {
+                        if self.source.is_some() {
+                            div0.cursor_crosshair()
+                                .child(EyedropperElement::new(cx.view()))
+                        } else {
+                            div0.flex_center()
+                                .text_color(palette.at_darkness(500))
+                                .child("Open a PNG or JPEG, or paste an image from the clipboard.")
+                        }
+                    }))
+                    .child(side_panel),
+            )
+    }
+}
+
+pub struct EyedropperElement {
+    view: View<Eyedropper>,
+    interactivity: Interactivity,
+}
+
+impl StatefulInteractiveElement for EyedropperElement {}
+
+impl InteractiveElement for EyedropperElement {
+    fn interactivity(&mut self) -> &mut Interactivity {
+        &mut self.interactivity
+    }
+}
+
+impl IntoElement for EyedropperElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl EyedropperElement {
+    pub fn new(view: &View<Eyedropper>) -> Self {
+        Self {
+            view: view.clone(),
+            interactivity: Default::default(),
+        }
+    }
+}
+
+impl Element for EyedropperElement {
+    type BeforeLayout = ();
+    type AfterLayout = Option<(Bounds<Pixels>, Arc<ImageData>)>;
+
+    fn before_layout(&mut self, cx: &mut ElementContext) -> (LayoutId, Self::BeforeLayout) {
+        let layout_id = self.interactivity.before_layout(cx, |mut style, cx| {
+            style.size.width = relative(1.).into();
+            style.size.height = relative(1.).into();
+            cx.request_layout(&style, None)
+        });
+        (layout_id, ())
+    }
+
+    fn after_layout(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        cx: &mut ElementContext,
+    ) -> Self::AfterLayout {
+        self.view.update(cx, |view, _| {
+            let source = view.source.as_ref()?;
+            let image_bounds = Eyedropper::fit(bounds, source.image.dimensions());
+            view.image_bounds = Some(image_bounds);
+            Some((image_bounds, source.image_data.clone()))
+        })
+    }
+
+    fn paint(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        after_layout: &mut Self::AfterLayout,
+        cx: &mut ElementContext,
+    ) {
+        let Some((image_bounds, data)) = after_layout else {
+            return;
+        };
+        cx.on_mouse_event({
+            let view = self.view.clone();
+            move |ev: &MouseMoveEvent, phase, cx| {
+                view.update(cx, |view, cx| view.hover(cx, ev.position));
+            }
+        });
+        cx.on_mouse_event({
+            let view = self.view.clone();
+            move |ev: &MouseDownEvent, phase, cx| {
+                if ev.button == MouseButton::Left {
+                    view.update(cx, |view, cx| view.pick(cx, ev.position));
+                }
+            }
+        });
+        cx.paint_image(*image_bounds, Corners::all(px(0.)), data.clone(), false)
+            .log_err();
+    }
+}
@@ -0,0 +1,502 @@
+use super::*;
+use crate::{
+    label_color, AppState, ColorFormat, Gamut, GamutMapping, Gradient, GradientShape, Harmony,
+    Interpolation, ResultExt,
+};
+use gpui::*;
+use gpui_ext::*;
+use image::{Bgra, ImageBuffer};
+use std::sync::Arc;
+
+/// Builds a gradient from the current color and others, and exports it as CSS or color steps.
+pub struct GradientEditor {
+    workspace: WeakView<Workspace>,
+    app_state: Arc<AppState>,
+    gradient: Gradient,
+    selected: usize, // index of the stop being edited
+    step_count: usize,
+    preview_size: Option<(u32, u32)>, // in device pixels
+    preview: Option<Arc<ImageData>>,  // None when the gradient changed since it was drawn
+    message: Option<SharedString>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GradientEditor {
+    const ANGLES: [f32; 8] = [0., 45., 90., 135., 180., 225., 270., 315.];
+    const LUT_SIZE: usize = 512;
+    const MAX_STEPS: usize = 32;
+    const NUDGE: f32 = 0.05;
+
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
+        app_state: Arc<AppState>,
+    ) -> Self {
+        // start from the current color to its complement
+        let color = *app_state.color.read(cx);
+        let complement = Harmony::Complementary.colors(color)[0];
+        let _subscriptions = vec![
+            cx.observe(&app_state.color, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.color_vision, |this, _, cx| {
+                this.preview = None;
+                cx.notify();
+            }),
+        ];
+        Self {
+            workspace,
+            app_state,
+            gradient: Gradient::new(color, complement),
+            selected: 0,
+            step_count: 7,
+            preview_size: None,
+            preview: None,
+            message: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
+
+    fn update_gradient(&mut self, cx: &mut ViewContext<Self>, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.preview = None;
+        self.message = None;
+        cx.notify();
+    }
+
+    fn add_stop(&mut self, cx: &mut ViewContext<Self>) {
+        // halfway to the next stop, or to the previous one from the last stop
+        let stops = self.gradient.stops();
+        let (a, b) = match stops.get(self.selected + 1) {
+            Some(next) => (stops[self.selected].position, next.position),
+            None => (
+                stops[self.selected - 1].position,
+                stops[self.selected].position,
+            ),
+        };
+        self.update_gradient(cx, |this| {
+            this.selected = this.gradient.add_stop((a + b) / 2.)
+        });
+    }
+
+    fn remove_stop(&mut self, cx: &mut ViewContext<Self>) {
+        self.update_gradient(cx, |this| {
+            if this.gradient.remove_stop(this.selected) {
+                this.selected = this.selected.min(this.gradient.stops().len() - 1);
+            }
+        });
+    }
+
+    fn nudge_stop(&mut self, cx: &mut ViewContext<Self>, delta: f32) {
+        self.update_gradient(cx, |this| {
+            let position = this.gradient.stops()[this.selected].position + delta;
+            this.selected = this.gradient.set_stop_position(this.selected, position);
+        });
+    }
+
+    fn set_stop_color(&mut self, cx: &mut ViewContext<Self>) {
+        let color = *self.app_state.color.read(cx);
+        self.update_gradient(cx, |this| {
+            this.gradient.set_stop_color(this.selected, color)
+        });
+    }
+
+    fn use_stop_color(&mut self, cx: &mut ViewContext<Self>) {
+        let color = self.gradient.stops()[self.selected].color;
+        self.workspace
+            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+            .log_err();
+    }
+
+    fn copy_css(&mut self, cx: &mut ViewContext<Self>) {
+        cx.write_to_clipboard(ClipboardItem::new(self.gradient.to_css()));
+        self.message = Some("CSS copied".into());
+        cx.notify();
+    }
+
+    fn copy_steps(&mut self, cx: &mut ViewContext<Self>) {
+        let steps = self.gradient.steps(self.step_count);
+        let text: Vec<String> = steps.iter().map(|x| x.to_string()).collect();
+        cx.write_to_clipboard(ClipboardItem::new(text.join("\n")));
+        self.message = Some(format!("{} colors copied", steps.len()).into());
+        cx.notify();
+    }
+
+    /// The gradient drawn over a checkerboard, with colors out of sRGB brought into it.
+    fn gen_image(&self, w: u32, h: u32, cx: &AppContext) -> ImageData {
+        // converting colors is slow, so look them up in a table along the gradient
+        let vision = *self.app_state.color_vision.read(cx);
+        let lut: Vec<([f32; 3], f32)> = (0..Self::LUT_SIZE)
+            .map(|i| {
+                let color = self.gradient.at(i as f32 / (Self::LUT_SIZE - 1) as f32);
+                let srgb = Gamut::Srgb
+                    .map(color, GamutMapping::ReduceChroma)
+                    .to_srgb()
+                    .map(|x| x.clamp(0., 1.));
+                (vision.simulate_srgb(srgb), color.alpha())
+            })
+            .collect();
+        let buffer: ImageBuffer<Bgra<u8>, Vec<u8>> = ImageBuffer::from_fn(w, h, |x, y| {
+            let t = self
+                .gradient
+                .position_at(x as f32 + 0.5, y as f32 + 0.5, w as f32, h as f32);
+            let ([r, g, b], alpha) = lut[(t * (Self::LUT_SIZE - 1) as f32).round() as usize];
+            let bg = checkerboard(x, y, 12);
+            let blend = |c: f32| c * alpha + bg * (1. - alpha);
+            Bgra([
+                channel_byte(blend(b)),
+                channel_byte(blend(g)),
+                channel_byte(blend(r)),
+                255,
+            ])
+        });
+        ImageData::new(buffer)
+    }
+
+    fn render_stop_bar(&self, cx: &mut ViewContext<Self>) -> Div {
+        div()
+            .relative()
+            .w_full()
+            .h(px(28.))
+            .children(self.gradient.stops().iter().enumerate().map(|(i, stop)| {
+                let selected = i == self.selected;
+                div()
+                    .id(("gradient-stop", i))
+                    .absolute()
+                    .top(px(0.))
+                    .left(relative(stop.position))
+                    .ml(px(-8.))
+                    .w(px(16.))
+                    .h(px(28.))
+                    .rounded(px(4.))
+                    .border_2()
+                    .border_color(if selected {
+                        rgb(0xffffff)
+                    } else {
+                        rgb(0x555555)
+                    })
+                    .bg(stop.color.canonicalize())
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.selected = i;
+                        cx.notify();
+                    }))
+            }))
+    }
+
+    fn render_stop_controls(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let stop = self.gradient.stops()[self.selected];
+        let can_remove = self.gradient.stops().len() > 2;
+
+        div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap(px(8.))
+            .child(
+                div()
+                    .w(px(200.))
+                    .font("Monaspace Xenon")
+                    .text_size(px(14.))
+                    .text_color(palette.at_darkness(200))
+                    .child(format!(
+                        "{}  {:.0}%",
+                        stop.color.to_rgb().to_string().to_uppercase(),
+                        stop.position * 100.
+                    )),
+            )
+            .child(
+                option_button("stop-left", "◀", false, &palette)
+                    .on_click(cx.listener(|this, _, cx| this.nudge_stop(cx, -Self::NUDGE))),
+            )
+            .child(
+                option_button("stop-right", "▶", false, &palette)
+                    .on_click(cx.listener(|this, _, cx| this.nudge_stop(cx, Self::NUDGE))),
+            )
+            .child(
+                option_button("stop-set-color", "Set to current color", false, &palette)
+                    .on_click(cx.listener(|this, _, cx| this.set_stop_color(cx))),
+            )
+            .child(
+                option_button("stop-use-color", "Use", false, &palette)
+                    .on_click(cx.listener(|this, _, cx| this.use_stop_color(cx))),
+            )
+            .child(
+                option_button("stop-add", "Add stop", false, &palette)
+                    .on_click(cx.listener(|this, _, cx| this.add_stop(cx))),
+            )
+            .when(can_remove, |div0| {
+                div0.child(
+                    option_button("stop-remove", "Remove", false, &palette)
+                        .on_click(cx.listener(|this, _, cx| this.remove_stop(cx))),
+                )
+            })
+    }
+
+    fn render_options(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let row = |label: &'static str| {
+            div().flex().flex_row().items_center().gap(px(8.)).child(
+                div()
+                    .w(px(100.))
+                    .text_size(px(13.))
+                    .text_color(palette.at_darkness(400))
+                    .child(label),
+            )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(8.))
+            .child(
+                row("Interpolate in").children(Interpolation::ALL.into_iter().map(
+                    |interpolation| {
+                        let selected = self.gradient.interpolation == interpolation;
+                        option_button(
+                            ("interpolation", interpolation as usize),
+                            interpolation.name(),
+                            selected,
+                            &palette,
+                        )
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.update_gradient(cx, |this| {
+                                this.gradient.interpolation = interpolation
+                            })
+                        }))
+                    },
+                )),
+            )
+            .child(
+                row("Shape").children(GradientShape::ALL.into_iter().map(|shape| {
+                    let selected = self.gradient.shape == shape;
+                    option_button(("shape", shape as usize), shape.name(), selected, &palette)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.update_gradient(cx, |this| this.gradient.shape = shape)
+                        }))
+                })),
+            )
+            .when(self.gradient.shape == GradientShape::Linear, |div0| {
+                div0.child(
+                    row("Angle").children(Self::ANGLES.into_iter().enumerate().map(
+                        |(i, angle)| {
+                            let selected = self.gradient.angle == angle;
+                            option_button(("angle", i), format!("{}°", angle), selected, &palette)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.update_gradient(cx, |this| this.gradient.angle = angle)
+                                }))
+                        },
+                    )),
+                )
+            })
+    }
+
+    fn render_export(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let vision = *self.app_state.color_vision.read(cx);
+        let steps = self.gradient.steps(self.step_count);
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(8.))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(px(8.))
+                    .child(
+                        option_button("copy-css", "Copy CSS", false, &palette)
+                            .on_click(cx.listener(|this, _, cx| this.copy_css(cx))),
+                    )
+                    .child(
+                        option_button("copy-steps", "Copy steps", false, &palette)
+                            .on_click(cx.listener(|this, _, cx| this.copy_steps(cx))),
+                    )
+                    .child(option_button("fewer-steps", "−", false, &palette).on_click(
+                        cx.listener(|this, _, cx| {
+                            this.step_count = (this.step_count - 1).max(2);
+                            cx.notify();
+                        }),
+                    ))
+                    .child(
+                        div()
+                            .text_size(px(13.))
+                            .text_color(palette.at_darkness(300))
+                            .child(format!("{} steps", self.step_count)),
+                    )
+                    .child(option_button("more-steps", "+", false, &palette).on_click(
+                        cx.listener(|this, _, cx| {
+                            this.step_count = (this.step_count + 1).min(Self::MAX_STEPS);
+                            cx.notify();
+                        }),
+                    )),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .rounded(px(4.))
+                    .overflow_hidden()
+                    .children(steps.into_iter().enumerate().map(|(i, color)| {
+                        let bg = vision.simulate_hsla(color.canonicalize());
+                        div()
+                            .id(("gradient-step", i))
+                            .flex_1()
+                            .h(px(40.))
+                            .bg(bg)
+                            .text_color(label_color(bg))
+                            .text_size(px(10.))
+                            .flex_center()
+                            .cursor_pointer()
+                            .child(color.to_string())
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.workspace
+                                    .update(cx, |workspace, cx| {
+                                        workspace.set_color_format(cx, color)
+                                    })
+                                    .log_err();
+                            }))
+                    })),
+            )
+            .child(
+                div()
+                    .font("Monaspace Xenon")
+                    .text_size(px(13.))
+                    .text_color(palette.at_darkness(300))
+                    .child(self.gradient.to_css()),
+            )
+    }
+}
+
+impl FocusableView for GradientEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for GradientEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let palette = *self.app_state.palette.read(cx);
+        let stop_bar = self.render_stop_bar(cx);
+        let stop_controls = self.render_stop_controls(cx);
+        let options = self.render_options(cx);
+        let export = self.render_export(cx);
+
+        div()
+            .id("gradient-editor")
+            .size_full()
+            .overflow_y_scroll()
+            .bg(rgb(0x000000))
+            .border_size(rems(2.), rems(2.), rems(2.), rems(2.))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .text_size(rems(1.5))
+                    .text_color(palette.at_darkness(300))
+                    .child("Gradients"),
+            )
+            .when_some(self.message.clone(), |div0, message| {
+                div0.child(
+                    div()
+                        .text_size(px(13.))
+                        .text_color(palette.at_darkness(300))
+                        .child(message),
+                )
+            })
+            .child(
+                div()
+                    .w_full()
+                    .h(px(160.))
+                    .rounded(px(8.))
+                    .overflow_hidden()
+                    .child(GradientPreviewElement::new(cx.view())),
+            )
+            .child(stop_bar)
+            .child(stop_controls)
+            .child(options)
+            .child(export)
+    }
+}
+
+pub struct GradientPreviewElement {
+    view: View<GradientEditor>,
+    interactivity: Interactivity,
+}
+
+impl StatefulInteractiveElement for GradientPreviewElement {}
+
+impl InteractiveElement for GradientPreviewElement {
+    fn interactivity(&mut self) -> &mut Interactivity {
+        &mut self.interactivity
+    }
+}
+
+impl IntoElement for GradientPreviewElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl GradientPreviewElement {
+    pub fn new(view: &View<GradientEditor>) -> Self {
+        Self {
+            view: view.clone(),
+            interactivity: Default::default(),
+        }
+    }
+}
+
+impl Element for GradientPreviewElement {
+    type BeforeLayout = ();
+    type AfterLayout = Option<Arc<ImageData>>;
+
+    fn before_layout(&mut self, cx: &mut ElementContext) -> (LayoutId, Self::BeforeLayout) {
+        let layout_id = self.interactivity.before_layout(cx, |mut style, cx| {
+            style.size.width = relative(1.).into();
+            style.size.height = relative(1.).into();
+            cx.request_layout(&style, None)
+        });
+        (layout_id, ())
+    }
+
+    fn after_layout(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        cx: &mut ElementContext,
+    ) -> Self::AfterLayout {
+        let sf = cx.scale_factor();
+        let Size { width, height } = bounds.size;
+        let (w, h): (u32, u32) = ((width * sf).into(), (height * sf).into());
+        if w == 0 || h == 0 {
+            return None;
+        }
+        self.view.update(cx, |view, cx| {
+            if view.preview.is_none() || view.preview_size != Some((w, h)) {
+                view.preview = Some(Arc::new(view.gen_image(w, h, cx)));
+                view.preview_size = Some((w, h));
+            }
+            view.preview.clone()
+        })
+    }
+
+    fn paint(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        before_layout: &mut Self::BeforeLayout,
+        after_layout: &mut Self::AfterLayout,
+        cx: &mut ElementContext,
+    ) {
+        if let Some(data) = after_layout {
+            cx.paint_image(bounds, Corners::all(px(0.)), data.clone(), false)
+                .log_err();
+        }
+    }
+}
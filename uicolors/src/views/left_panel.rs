@@ -1,4 +1,7 @@
-use crate::{AppState, Assets, Workspace};
+use crate::{
+    AppState, Assets, ColorFormat, ColorItem, ColorLibrary, DraggedColor, MainTab, ResultExt,
+    Workspace,
+};
 use gpui::*;
 use gpui_ext::*;
 use std::sync::Arc;
@@ -9,7 +12,12 @@ pub struct LeftPanel {
     btn_home: View<TopLevelButton>,
     btn_browse: View<TopLevelButton>,
     btn_favorites: View<TopLevelButton>,
+    btn_design_system: View<TopLevelButton>,
+    btn_eyedropper: View<TopLevelButton>,
+    btn_compare: View<TopLevelButton>,
+    btn_gradient: View<TopLevelButton>,
     focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl LeftPanel {
@@ -18,27 +26,119 @@ impl LeftPanel {
         workspace: WeakView<Workspace>,
         app_state: Arc<AppState>,
     ) -> Self {
+        let main_tab = app_state.main_tab.clone();
+        let _subscriptions = vec![cx.observe(&app_state.library, |_, _, cx| cx.notify())];
         Self {
-            app_state,
-            workspace,
-            btn_home: cx.new_view(|cx| TopLevelButton {
-                label: "Home".into(),
-                icon: Assets::icon("home").into(),
-                focus_handle: cx.focus_handle(),
+            btn_home: cx.new_view(|cx| {
+                TopLevelButton::new(cx, "Home", "home", MainTab::Home, main_tab.clone())
+            }),
+            btn_browse: cx.new_view(|cx| {
+                TopLevelButton::new(cx, "Browse", "book", MainTab::Browse, main_tab.clone())
+            }),
+            btn_favorites: cx.new_view(|cx| {
+                TopLevelButton::new(
+                    cx,
+                    "Favorites",
+                    "book-star",
+                    MainTab::Favorites,
+                    main_tab.clone(),
+                )
             }),
-            btn_browse: cx.new_view(|cx| TopLevelButton {
-                label: "Browse".into(),
-                icon: Assets::icon("book").into(),
-                focus_handle: cx.focus_handle(),
+            btn_design_system: cx.new_view(|cx| {
+                TopLevelButton::new(
+                    cx,
+                    "Design system",
+                    "layers",
+                    MainTab::DesignSystem,
+                    main_tab.clone(),
+                )
             }),
-            btn_favorites: cx.new_view(|cx| TopLevelButton {
-                label: "Favorites".into(),
-                icon: Assets::icon("book-star").into(),
-                focus_handle: cx.focus_handle(),
+            btn_eyedropper: cx.new_view(|cx| {
+                TopLevelButton::new(
+                    cx,
+                    "Eyedropper",
+                    "eyedropper",
+                    MainTab::Eyedropper,
+                    main_tab.clone(),
+                )
             }),
+            btn_compare: cx.new_view(|cx| {
+                TopLevelButton::new(cx, "Compare", "compare", MainTab::Compare, main_tab.clone())
+            }),
+            btn_gradient: cx.new_view(|cx| {
+                TopLevelButton::new(
+                    cx,
+                    "Gradients",
+                    "gradient",
+                    MainTab::Gradient,
+                    main_tab.clone(),
+                )
+            }),
+            app_state,
+            workspace,
             focus_handle: cx.focus_handle(),
+            _subscriptions,
         }
     }
+
+    /// Saves a color dropped on the library list, keeping the name it may already have there.
+    fn save_color(&mut self, cx: &mut ViewContext<Self>, color: ColorFormat) {
+        self.app_state.library.update(cx, |library, cx| {
+            library
+                .get_or_insert_with(ColorLibrary::default)
+                .import(vec![ColorItem::new(color.to_rgb(), None)]);
+            cx.notify();
+        });
+    }
+
+    fn render_library(&self, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        let items = self
+            .app_state
+            .library
+            .read(cx)
+            .as_ref()
+            .map(|x| x.items.clone())
+            .unwrap_or_default();
+        div()
+            .id("library-list")
+            .size_full()
+            .overflow_y_scroll()
+            .bg(rgb(0x888888))
+            .drag_over::<DraggedColor>(|st| st.bg(rgb(0xaaaadd)))
+            .on_drop(cx.listener(|this, dragged: &DraggedColor, cx| this.save_color(cx, dragged.0)))
+            .children(items.into_iter().enumerate().map(|(i, item)| {
+                let color = item.color;
+                div()
+                    .id(("library-list-item", i))
+                    .h(rems(1.5))
+                    .px(rems(0.5))
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(rems(0.4))
+                    .text_size(rems(0.7))
+                    .cursor_pointer()
+                    .hover(|st| st.bg(rgb(0x9999aa)))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.workspace
+                            .update(cx, |workspace, cx| workspace.set_color_format(cx, color))
+                            .log_err();
+                    }))
+                    .child(
+                        div()
+                            .size(rems(0.9))
+                            .rounded(px(3.))
+                            .bg(color.canonicalize()),
+                    )
+                    .child(
+                        div().flex_1().child(
+                            item.name
+                                .unwrap_or_else(|| color.to_rgb().to_string().to_uppercase()),
+                        ),
+                    )
+                    .when(item.favorite, |div0| div0.child("★"))
+            }))
+    }
 }
 
 impl FocusableView for LeftPanel {
@@ -49,6 +149,7 @@ impl FocusableView for LeftPanel {
 
 impl Render for LeftPanel {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let library = self.render_library(cx);
         div()
             .w(rems(10.))
             .min_w(rems(10.))
@@ -62,7 +163,11 @@ impl Render for LeftPanel {
                     .bg(rgb(0x8888FF))
                     .child(self.btn_home.clone())
                     .child(self.btn_browse.clone())
-                    .child(self.btn_favorites.clone()),
+                    .child(self.btn_favorites.clone())
+                    .child(self.btn_design_system.clone())
+                    .child(self.btn_eyedropper.clone())
+                    .child(self.btn_compare.clone())
+                    .child(self.btn_gradient.clone()),
             )
             .child(div().w_full().h(px(1.)).bg(rgb(0x888888)))
             .child(
@@ -74,14 +179,37 @@ impl Render for LeftPanel {
                     .child(div().child("Library").w_full())
                     .child(div().child("★")),
             )
-            .child(div().size_full().bg(rgb(0x888888)))
+            .child(library)
     }
 }
 
 struct TopLevelButton {
     label: SharedString,
     icon: SharedString,
+    tab: MainTab,
+    main_tab: Model<MainTab>,
     focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TopLevelButton {
+    fn new(
+        cx: &mut ViewContext<Self>,
+        label: &str,
+        icon: &str,
+        tab: MainTab,
+        main_tab: Model<MainTab>,
+    ) -> Self {
+        let _subscriptions = vec![cx.observe(&main_tab, |_, _, cx| cx.notify())];
+        Self {
+            label: SharedString::from(label.to_owned()),
+            icon: Assets::icon(icon).into(),
+            tab,
+            main_tab,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
 }
 
 impl FocusableView for TopLevelButton {
@@ -92,16 +220,30 @@ impl FocusableView for TopLevelButton {
 
 impl Render for TopLevelButton {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let selected = *self.main_tab.read(cx) == self.tab;
         div()
+            .id(self.label.clone())
             .w_full()
-            .bg(rgb(0x8888FF))
+            .bg(if selected {
+                rgb(0x444488)
+            } else {
+                rgb(0x8888FF)
+            })
             .border_y_size(rems(0.1), rems(0.1))
             .border_x_size(rems(0.6), rems(0.6))
             .rounded_md()
             .flex()
             .flex_row()
+            .cursor_pointer()
             .hover(|st| st.bg(rgb(0x000000)))
             .text_color(rgb(0xffff88))
+            .on_click(cx.listener(|this, _, cx| {
+                let tab = this.tab;
+                this.main_tab.update(cx, |main_tab, cx| {
+                    *main_tab = tab;
+                    cx.notify();
+                });
+            }))
             .child(
                 div()
                     .size(rems(1.5))
@@ -0,0 +1,442 @@
+use super::*;
+use crate::{
+    import_file, label_color, AppState, ColorFormat, ColorItem, ColorLibrary, MainTab, ResultExt,
+    SCALES,
+};
+use gpui::*;
+use gpui_ext::*;
+use std::sync::Arc;
+
+/// Grid of the library colors, used by both the Browse and the Favorites tabs.
+pub struct LibraryGrid {
+    workspace: WeakView<Workspace>,
+    app_state: Arc<AppState>,
+    favorites_only: bool,
+    renaming: Option<(ColorFormat, View<TextField>, Subscription)>, // by color, indices shift
+    deleted: Option<(usize, ColorItem)>,                            // last deleted item, for undo
+    import_report: Option<(usize, Vec<(String, String)>)>,          // (added, skipped entries)
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LibraryGrid {
+    const CARD_WIDTH: f32 = 200.;
+
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
+        app_state: Arc<AppState>,
+        favorites_only: bool,
+    ) -> Self {
+        let _subscriptions = vec![
+            cx.observe(&app_state.library, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.palette, |_, _, cx| cx.notify()),
+        ];
+        Self {
+            workspace,
+            app_state,
+            favorites_only,
+            renaming: None,
+            deleted: None,
+            import_report: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
+
+    fn load_color(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        let Some(item) = self.item(cx, index) else {
+            return;
+        };
+        let color = item.color.canonicalize();
+        self.workspace
+            .update(cx, |workspace, cx| workspace.set_color(cx, color))
+            .log_err();
+        self.app_state.main_tab.update(cx, |main_tab, cx| {
+            *main_tab = MainTab::Home;
+            cx.notify();
+        });
+    }
+
+    fn toggle_favorite(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        self.app_state.library.update(cx, |library, cx| {
+            if let Some(item) = library.as_mut().and_then(|x| x.items.get_mut(index)) {
+                item.favorite = !item.favorite;
+                cx.notify();
+            }
+        });
+    }
+
+    fn start_rename(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        let Some(item) = self.item(cx, index) else {
+            return;
+        };
+        let color = item.color;
+        let field = cx.new_view(|cx| TextField::new(cx, item.name.unwrap_or_default(), "Name"));
+        let subscription = cx.subscribe(&field, move |this, _, event, cx| match event {
+            TextFieldEvent::Submit(name) => {
+                let name = name.trim().to_owned();
+                let name = (!name.is_empty()).then_some(name);
+                this.app_state.library.update(cx, |library, cx| {
+                    if let Some(library) = library {
+                        if let Some(index) = library.position(color) {
+                            library.rename(index, name);
+                            cx.notify();
+                        }
+                    }
+                });
+                this.renaming = None;
+                cx.notify();
+            }
+            TextFieldEvent::Cancel => {
+                this.renaming = None;
+                cx.notify();
+            }
+            TextFieldEvent::Changed(_) => {}
+        });
+        cx.focus_view(&field);
+        self.renaming = Some((color, field, subscription));
+        cx.notify();
+    }
+
+    fn delete(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        let deleted = self.app_state.library.update(cx, |library, cx| {
+            let item = library.as_mut()?.remove(index)?;
+            cx.notify();
+            Some(item)
+        });
+        if let Some(item) = deleted {
+            self.deleted = Some((index, item));
+            self.renaming = None;
+            cx.notify();
+        }
+    }
+
+    fn undo_delete(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((index, item)) = self.deleted.take() else {
+            return;
+        };
+        self.app_state.library.update(cx, |library, cx| {
+            library
+                .get_or_insert_with(ColorLibrary::default)
+                .restore(index, item);
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn import(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Some(paths)) = paths.await else {
+                return;
+            };
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                let (mut added, mut skipped) = (0, vec![]);
+                for path in paths {
+                    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                    match import_file(&path) {
+                        Ok(report) => {
+                            this.app_state.library.update(cx, |library, cx| {
+                                added += library
+                                    .get_or_insert_with(ColorLibrary::default)
+                                    .import(report.items);
+                                cx.notify();
+                            });
+                            skipped.extend(report.skipped.into_iter().map(|(entry, reason)| {
+                                (format!("{}: {}", file_name, entry), reason)
+                            }));
+                        }
+                        Err(err) => skipped.push((file_name.into_owned(), format!("{:#}", err))),
+                    }
+                }
+                this.import_report = Some((added, skipped));
+                cx.notify();
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    fn render_import_report(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        const MAX_SHOWN: usize = 8;
+        let (added, skipped) = self.import_report.as_ref()?;
+        let palette = *self.app_state.palette.read(cx);
+        let summary = match skipped.len() {
+            0 => format!("Imported {} colors.", added),
+            n => format!("Imported {} colors, {} entries skipped:", added, n),
+        };
+        let more = skipped.len().saturating_sub(MAX_SHOWN);
+
+        Some(
+            div()
+                .flex()
+                .flex_col()
+                .gap(px(2.))
+                .text_size(px(13.))
+                .text_color(palette.at_darkness(300))
+                .child(
+                    div().flex().flex_row().gap(px(12.)).child(summary).child(
+                        div()
+                            .id("dismiss-import-report")
+                            .cursor_pointer()
+                            .text_color(palette.at_darkness(500))
+                            .child("Dismiss")
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.import_report = None;
+                                cx.notify();
+                            })),
+                    ),
+                )
+                .children(skipped.iter().take(MAX_SHOWN).map(|(entry, reason)| {
+                    div()
+                        .text_size(px(12.))
+                        .text_color(palette.at_darkness(500))
+                        .child(format!("{}  {}", entry, reason))
+                }))
+                .when(more > 0, |div0| {
+                    div0.child(
+                        div()
+                            .text_size(px(12.))
+                            .text_color(palette.at_darkness(500))
+                            .child(format!("and {} more", more)),
+                    )
+                }),
+        )
+    }
+
+    fn item(&self, cx: &AppContext, index: usize) -> Option<ColorItem> {
+        let library = self.app_state.library.read(cx).as_ref()?;
+        library.items.get(index).cloned()
+    }
+
+    fn render_card(
+        &self,
+        cx: &mut ViewContext<Self>,
+        index: usize,
+        item: &ColorItem,
+    ) -> Stateful<Div> {
+        let ui_palette = *self.app_state.palette.read(cx);
+        let palette = ui_palette.with_base(item.color.canonicalize());
+        let name = item.name.clone().unwrap_or_else(|| "Untitled".to_owned());
+        let hex = item.color.to_rgb().to_string().to_uppercase();
+        let rename_field = match &self.renaming {
+            Some((color, field, _)) if color.to_rgb() == item.color.to_rgb() => Some(field.clone()),
+            _ => None,
+        };
+        let action = |id: &'static str, label: &'static str| {
+            div()
+                .id((id, index))
+                .size(px(20.))
+                .flex_center()
+                .rounded(px(4.))
+                .text_size(px(12.))
+                .text_color(ui_palette.at_darkness(400))
+                .hover(|st| st.bg(ui_palette.at_darkness(700)))
+                .child(label)
+        };
+
+        div()
+            .id(("library-item", index))
+            .w(px(Self::CARD_WIDTH))
+            .rounded(px(8.))
+            .bg(ui_palette.at_darkness(900))
+            .text_color(ui_palette.at_darkness(200))
+            .cursor_pointer()
+            .hover(|st| st.bg(ui_palette.at_darkness(800)))
+            .on_click(cx.listener(move |this, _, cx| this.load_color(cx, index)))
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .h(px(64.))
+                    .rounded_t(px(8.))
+                    .bg(item.color.canonicalize())
+                    .flex()
+                    .flex_row()
+                    .justify_end()
+                    .child(
+                        div()
+                            .id(("favorite", index))
+                            .size(px(28.))
+                            .flex_center()
+                            .text_size(px(18.))
+                            .text_color(label_color(item.color.canonicalize()))
+                            .child(if item.favorite { "★" } else { "☆" })
+                            .on_click(cx.listener(move |this, _, cx| {
+                                cx.stop_propagation();
+                                this.toggle_favorite(cx, index);
+                            })),
+                    ),
+            )
+            .child(div().h(px(12.)).flex().flex_row().children({
+                // imported families keep their own shades
+                let shades: Vec<Hsla> = if item.shades.is_empty() {
+                    SCALES.iter().map(|sc| palette.at_darkness(*sc)).collect()
+                } else {
+                    item.shades.values().map(|c| c.canonicalize()).collect()
+                };
+                shades.into_iter().map(|c| div().h_full().flex_1().bg(c))
+            }))
+            .child(
+                div()
+                    .border_size(px(8.), px(10.), px(8.), px(10.))
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .gap(px(4.))
+                    .text_size(px(13.))
+                    .map(|div0| match rename_field {
+                        // keep clicks in the field from loading the color
+                        Some(field) => div0.child(
+                            div()
+                                .id(("rename-field", index))
+                                .flex_1()
+                                .on_click(|_, cx| cx.stop_propagation())
+                                .child(field),
+                        ),
+                        None => div0.child(div().flex_1().child(name)),
+                    })
+                    .child(
+                        div()
+                            .font("Monaspace Xenon")
+                            .text_color(ui_palette.at_darkness(400))
+                            .child(hex),
+                    ),
+            )
+            .child(
+                div()
+                    .border_size(px(0.), px(6.), px(6.), px(6.))
+                    .flex()
+                    .flex_row()
+                    .justify_end()
+                    .gap(px(2.))
+                    .child(
+                        action("rename", "✎").on_click(cx.listener(move |this, _, cx| {
+                            cx.stop_propagation();
+                            this.start_rename(cx, index);
+                        })),
+                    )
+                    .child(
+                        action("delete", "✕").on_click(cx.listener(move |this, _, cx| {
+                            cx.stop_propagation();
+                            this.delete(cx, index);
+                        })),
+                    ),
+            )
+    }
+}
+
+impl FocusableView for LibraryGrid {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LibraryGrid {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let items: Vec<(usize, ColorItem)> = match self.app_state.library.read(cx) {
+            Some(library) => library
+                .items
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(_, item)| !self.favorites_only || item.favorite)
+                .collect(),
+            None => vec![],
+        };
+        let palette = *self.app_state.palette.read(cx);
+        let import_report = self.render_import_report(cx);
+        let (title, empty_msg) = if self.favorites_only {
+            (
+                "Favorites",
+                "No favorites yet. Star a color in Browse to see it here.",
+            )
+        } else {
+            ("Browse", "The library is empty.")
+        };
+
+        div()
+            .id("library-grid")
+            .size_full()
+            .overflow_y_scroll()
+            .bg(rgb(0x000000))
+            .border_size(rems(2.), rems(2.), rems(2.), rems(2.))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_size(rems(1.5))
+                            .text_color(palette.at_darkness(300))
+                            .child(title),
+                    )
+                    .when(!self.favorites_only, |div0| {
+                        div0.child(
+                            div()
+                                .id("import")
+                                .h(px(28.))
+                                .px(px(12.))
+                                .flex_center()
+                                .rounded(px(4.))
+                                .cursor_pointer()
+                                .text_size(px(13.))
+                                .text_color(palette.at_darkness(300))
+                                .bg(palette.at_darkness(900))
+                                .hover(|st| st.bg(palette.at_darkness(800)))
+                                .child("Import…")
+                                .on_click(cx.listener(|this, _, cx| this.import(cx))),
+                        )
+                    }),
+            )
+            .children(import_report)
+            .when_some(self.deleted.as_ref(), |div0, (_, item)| {
+                let name = item.name.clone().unwrap_or_else(|| "Untitled".to_owned());
+                div0.child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap(px(12.))
+                        .text_size(px(13.))
+                        .text_color(palette.at_darkness(300))
+                        .child(format!("Deleted {}", name))
+                        .child(
+                            div()
+                                .id("undo-delete")
+                                .px(px(8.))
+                                .rounded(px(4.))
+                                .cursor_pointer()
+                                .bg(palette.at_darkness(800))
+                                .hover(|st| st.bg(palette.at_darkness(700)))
+                                .child("Undo")
+                                .on_click(cx.listener(|this, _, cx| this.undo_delete(cx))),
+                        ),
+                )
+            })
+            .when(items.is_empty(), |div0| {
+                div0.child(div().text_color(palette.at_darkness(500)).child(empty_msg))
+            })
+            .child(
+                div().flex().flex_row().flex_wrap().gap(px(16.)).children(
+                    items
+                        .iter()
+                        .map(|(index, item)| self.render_card(cx, *index, item)),
+                ),
+            )
+    }
+}
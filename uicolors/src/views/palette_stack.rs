@@ -0,0 +1,324 @@
+use super::*;
+use crate::{
+    export_palettes, AppState, DesignSystem, ExportFormat, MainTab, PaletteRole, ResultExt, SCALES,
+};
+use gpui::*;
+use gpui_ext::*;
+use std::{path::PathBuf, sync::Arc};
+
+/// The palettes of the design system stacked on top of each other, with their roles.
+pub struct PaletteStack {
+    workspace: WeakView<Workspace>,
+    app_state: Arc<AppState>,
+    project_path: Option<PathBuf>, // last project file opened or saved
+    message: Option<SharedString>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PaletteStack {
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        workspace: WeakView<Workspace>,
+        app_state: Arc<AppState>,
+    ) -> Self {
+        let _subscriptions = vec![
+            cx.observe(&app_state.design_system, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.color_vision, |_, _, cx| cx.notify()),
+        ];
+        Self {
+            workspace,
+            app_state,
+            project_path: None,
+            message: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
+
+    fn edit_palette(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        self.workspace
+            .update(cx, |workspace, cx| workspace.select_palette(cx, index))
+            .log_err();
+        self.app_state.main_tab.update(cx, |main_tab, cx| {
+            *main_tab = MainTab::Home;
+            cx.notify();
+        });
+    }
+
+    fn add_role(&mut self, cx: &mut ViewContext<Self>, role: PaletteRole) {
+        self.app_state
+            .design_system
+            .update(cx, |design_system, cx| {
+                design_system.add_role(role);
+                cx.notify();
+            });
+    }
+
+    fn remove_palette(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        let active = self.app_state.design_system.read(cx).active();
+        self.app_state
+            .design_system
+            .update(cx, |design_system, cx| {
+                design_system.remove(index);
+                cx.notify();
+            });
+        // the edited color moves on to the palette that became active
+        if index == active {
+            let active = self.app_state.design_system.read(cx).active();
+            self.workspace
+                .update(cx, |workspace, cx| workspace.select_palette(cx, active))
+                .log_err();
+        }
+    }
+
+    fn set_message(&mut self, cx: &mut ViewContext<Self>, message: impl Into<SharedString>) {
+        self.message = Some(message.into());
+        cx.notify();
+    }
+
+    fn default_dir(&self) -> PathBuf {
+        match self.project_path.as_ref().and_then(|x| x.parent()) {
+            Some(dir) => dir.to_owned(),
+            None => dirs::document_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn open_project(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Some(paths)) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| match DesignSystem::load(&path) {
+                Ok(design_system) => {
+                    this.workspace
+                        .update(cx, |workspace, cx| {
+                            workspace.set_design_system(cx, design_system)
+                        })
+                        .log_err();
+                    this.set_message(cx, format!("Opened {}", path.display()));
+                    this.project_path = Some(path);
+                }
+                Err(err) => this.set_message(cx, format!("{:#}", err)),
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    fn save_project(&mut self, cx: &mut ViewContext<Self>) {
+        let design_system = self.app_state.design_system.read(cx).clone();
+        let path = cx.prompt_for_new_path(&self.default_dir());
+        cx.spawn(|this, mut cx| async move {
+            let Ok(Some(mut path)) = path.await else {
+                return;
+            };
+            if path.extension().is_none() {
+                path.set_extension(DesignSystem::EXTENSION);
+            }
+            let result = design_system.save(&path);
+            let Some(this) = this.upgrade() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| match result {
+                Ok(()) => {
+                    this.set_message(cx, format!("Saved to {}", path.display()));
+                    this.project_path = Some(path);
+                }
+                Err(err) => this.set_message(cx, format!("{:#}", err)),
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    fn copy_json(&mut self, cx: &mut ViewContext<Self>) {
+        let palettes = self.app_state.design_system.read(cx).export_palettes();
+        let json = export_palettes(&palettes, ExportFormat::Json);
+        cx.write_to_clipboard(ClipboardItem::new(json));
+        self.set_message(cx, format!("{} palettes copied as JSON", palettes.len()));
+    }
+
+    fn render_row(
+        &self,
+        cx: &mut ViewContext<Self>,
+        design_system: &DesignSystem,
+        index: usize,
+    ) -> Stateful<Div> {
+        let ui_palette = *self.app_state.palette.read(cx);
+        let vision = *self.app_state.color_vision.read(cx);
+        let spec = &design_system.palettes[index];
+        let palette = spec.palette();
+        let active = index == design_system.active();
+        let role = design_system.role_of(index).map(|x| x.name()).unwrap_or("");
+        let removable = design_system.palettes.len() > 1;
+
+        div()
+            .id(("palette-row", index))
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap(px(12.))
+            .px(px(12.))
+            .py(px(8.))
+            .rounded(px(8.))
+            .cursor_pointer()
+            .bg(if active {
+                ui_palette.at_darkness(800)
+            } else {
+                ui_palette.at_darkness(900)
+            })
+            .hover(|st| st.bg(ui_palette.at_darkness(800)))
+            .on_click(cx.listener(move |this, _, cx| this.edit_palette(cx, index)))
+            .child(
+                div()
+                    .w(px(120.))
+                    .flex()
+                    .flex_col()
+                    .child(
+                        div()
+                            .text_size(px(14.))
+                            .text_color(ui_palette.at_darkness(200))
+                            .child(spec.name.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(12.))
+                            .text_color(ui_palette.at_darkness(500))
+                            .child(role),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(4.))
+                    .children(SCALES.iter().map(|sc| {
+                        div()
+                            .w(px(56.))
+                            .h(px(40.))
+                            .rounded(px(4.))
+                            .bg(vision.simulate_hsla(palette.at_darkness(*sc)))
+                    })),
+            )
+            .when(removable, |div0| {
+                div0.child(
+                    div()
+                        .id(("remove-palette", index))
+                        .size(px(20.))
+                        .flex_center()
+                        .rounded(px(4.))
+                        .text_size(px(12.))
+                        .text_color(ui_palette.at_darkness(400))
+                        .hover(|st| st.bg(ui_palette.at_darkness(700)))
+                        .child("✕")
+                        .on_click(cx.listener(move |this, _, cx| {
+                            cx.stop_propagation();
+                            this.remove_palette(cx, index);
+                        })),
+                )
+            })
+    }
+}
+
+impl FocusableView for PaletteStack {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for PaletteStack {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let design_system = self.app_state.design_system.read(cx).clone();
+        let palette = *self.app_state.palette.read(cx);
+        let missing_roles: Vec<PaletteRole> = PaletteRole::ALL
+            .into_iter()
+            .filter(|x| design_system.palette_with_role(*x).is_none())
+            .collect();
+        let rows: Vec<Stateful<Div>> = (0..design_system.palettes.len())
+            .map(|i| self.render_row(cx, &design_system, i))
+            .collect();
+
+        div()
+            .id("palette-stack")
+            .size_full()
+            .overflow_y_scroll()
+            .bg(rgb(0x000000))
+            .border_size(rems(2.), rems(2.), rems(2.), rems(2.))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_size(rems(1.5))
+                            .text_color(palette.at_darkness(300))
+                            .child("Design system"),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .gap(px(8.))
+                            .child(
+                                option_button("open-project", "Open…", false, &palette)
+                                    .on_click(cx.listener(|this, _, cx| this.open_project(cx))),
+                            )
+                            .child(
+                                option_button("save-project", "Save…", false, &palette)
+                                    .on_click(cx.listener(|this, _, cx| this.save_project(cx))),
+                            )
+                            .child(
+                                option_button("copy-json", "Copy JSON", false, &palette)
+                                    .on_click(cx.listener(|this, _, cx| this.copy_json(cx))),
+                            ),
+                    ),
+            )
+            .when_some(self.message.clone(), |div0, message| {
+                div0.child(
+                    div()
+                        .text_size(px(13.))
+                        .text_color(palette.at_darkness(300))
+                        .child(message),
+                )
+            })
+            .child(div().flex().flex_col().gap(px(8.)).children(rows))
+            .when(!missing_roles.is_empty(), |div0| {
+                div0.child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap(px(8.))
+                        .child(
+                            div()
+                                .text_size(px(12.))
+                                .text_color(palette.at_darkness(500))
+                                .child("Add"),
+                        )
+                        .children(missing_roles.into_iter().map(|role| {
+                            option_button(role.name(), role.name(), false, &palette)
+                                .on_click(cx.listener(move |this, _, cx| this.add_role(cx, role)))
+                        })),
+                )
+            })
+    }
+}
@@ -0,0 +1,182 @@
+use super::*;
+use crate::{parse_range, AppState, RandomConfig};
+use gpui::*;
+use gpui_ext::*;
+use std::sync::Arc;
+
+/// Settings of the spacebar random colors: presets, OKLCH ranges, harmony and seed.
+pub struct RandomOptions {
+    app_state: Arc<AppState>,
+    hue_field: View<TextField>,
+    chroma_field: View<TextField>,
+    lightness_field: View<TextField>,
+    seed_field: View<TextField>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Hue,
+    Chroma,
+    Lightness,
+    Seed,
+}
+
+impl RandomOptions {
+    pub fn new(cx: &mut ViewContext<Self>, app_state: Arc<AppState>) -> Self {
+        let config = app_state.random.read(cx).config();
+        let hue_field = cx.new_view(|cx| TextField::new(cx, range_text(config.hue), "0-360"));
+        let chroma_field =
+            cx.new_view(|cx| TextField::new(cx, range_text(config.chroma), "0-0.37"));
+        let lightness_field =
+            cx.new_view(|cx| TextField::new(cx, range_text(config.lightness), "0-1"));
+        let seed_field = cx.new_view(|cx| TextField::new(cx, "", "random"));
+
+        let _subscriptions = vec![
+            cx.observe(&app_state.random, |_, _, cx| cx.notify()),
+            cx.observe(&app_state.palette, |_, _, cx| cx.notify()),
+            Self::subscribe_field(cx, &hue_field, Field::Hue),
+            Self::subscribe_field(cx, &chroma_field, Field::Chroma),
+            Self::subscribe_field(cx, &lightness_field, Field::Lightness),
+            Self::subscribe_field(cx, &seed_field, Field::Seed),
+        ];
+        Self {
+            app_state,
+            hue_field,
+            chroma_field,
+            lightness_field,
+            seed_field,
+            focus_handle: cx.focus_handle(),
+            _subscriptions,
+        }
+    }
+
+    fn subscribe_field(
+        cx: &mut ViewContext<Self>,
+        field_view: &View<TextField>,
+        field: Field,
+    ) -> Subscription {
+        cx.subscribe(field_view, move |this, field_view, event, cx| {
+            let TextFieldEvent::Changed(text) = event else {
+                return;
+            };
+            let mut config = this.app_state.random.read(cx).config();
+            let valid = match field {
+                Field::Hue => parse_range(text).map(|x| config.hue = x).is_some(),
+                Field::Chroma => parse_range(text).map(|x| config.chroma = x).is_some(),
+                Field::Lightness => parse_range(text).map(|x| config.lightness = x).is_some(),
+                Field::Seed if text.trim().is_empty() => {
+                    config.seed = None;
+                    true
+                }
+                Field::Seed => text.trim().parse().map(|x| config.seed = Some(x)).is_ok(),
+            };
+            field_view.update(cx, |field, cx| field.set_invalid(cx, !valid));
+            if valid {
+                this.set_config(cx, config);
+            }
+        })
+    }
+
+    fn set_config(&mut self, cx: &mut ViewContext<Self>, config: RandomConfig) {
+        self.app_state.random.update(cx, |random, cx| {
+            random.set_config(config);
+            cx.notify();
+        });
+    }
+
+    fn apply_preset(&mut self, cx: &mut ViewContext<Self>, preset: RandomConfig) {
+        let config = RandomConfig {
+            harmonious: self.app_state.random.read(cx).config().harmonious,
+            seed: self.app_state.random.read(cx).config().seed,
+            ..preset
+        };
+        for (field, range) in [
+            (&self.hue_field, config.hue),
+            (&self.chroma_field, config.chroma),
+            (&self.lightness_field, config.lightness),
+        ] {
+            field.update(cx, |field, cx| {
+                field.set_text(cx, range_text(range));
+                field.set_invalid(cx, false);
+            });
+        }
+        self.set_config(cx, config);
+    }
+}
+
+fn range_text((a, b): (f32, f32)) -> String {
+    format!("{}-{}", a, b)
+}
+
+impl FocusableView for RandomOptions {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for RandomOptions {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let palette = *self.app_state.palette.read(cx);
+        let config = self.app_state.random.read(cx).config();
+        let preset_name = config.preset_name();
+        let label = |text: &'static str| {
+            div()
+                .text_size(px(12.))
+                .text_color(palette.at_darkness(500))
+                .child(text)
+        };
+        let field = |field: &View<TextField>| div().w(px(80.)).child(field.clone());
+
+        div()
+            .flex_center()
+            .flex_col()
+            .gap(px(6.))
+            .child(
+                div()
+                    .flex_center()
+                    .flex_row()
+                    .gap(px(8.))
+                    .child(label("Random"))
+                    .children(RandomConfig::PRESETS.iter().map(|(name, preset)| {
+                        let preset = *preset;
+                        option_button(*name, *name, preset_name == Some(*name), &palette)
+                            .on_click(cx.listener(move |this, _, cx| this.apply_preset(cx, preset)))
+                    }))
+                    .child(
+                        option_button("harmonious", "Harmonious", config.harmonious, &palette)
+                            .on_click(cx.listener(|this, _, cx| {
+                                let mut config = this.app_state.random.read(cx).config();
+                                config.harmonious = !config.harmonious;
+                                this.set_config(cx, config);
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_center()
+                    .flex_row()
+                    .gap(px(8.))
+                    .child(label("H"))
+                    .child(field(&self.hue_field))
+                    .child(label("C"))
+                    .child(field(&self.chroma_field))
+                    .child(label("L"))
+                    .child(field(&self.lightness_field))
+                    .child(label("Seed"))
+                    .child(field(&self.seed_field))
+                    .when(config.seed.is_some(), |div0| {
+                        div0.child(
+                            option_button("restart", "Restart", false, &palette).on_click(
+                                cx.listener(|this, _, cx| {
+                                    this.app_state
+                                        .random
+                                        .update(cx, |random, _| random.restart());
+                                }),
+                            ),
+                        )
+                    }),
+            )
+    }
+}
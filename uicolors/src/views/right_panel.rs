@@ -1,16 +1,139 @@
 use super::*;
+use crate::{apca_contrast, AppState, ColorFormat, WcagResult, SCALES};
 use gpui::*;
+use gpui_ext::*;
+use std::sync::Arc;
 
 pub struct RightPanel {
+    app_state: Arc<AppState>,
+    selected: (usize, usize), // (text, background) indexes into SCALES
     focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl RightPanel {
-    pub fn new(cx: &mut ViewContext<Self>) -> Self {
+    const CELL_SIZE: f32 = 24.;
+
+    pub fn new(cx: &mut ViewContext<Self>, app_state: Arc<AppState>) -> Self {
+        let _subscriptions = vec![cx.observe(&app_state.palette, |_, _, cx| cx.notify())];
         Self {
+            app_state,
+            selected: (9, 1),
             focus_handle: cx.focus_handle(),
+            _subscriptions,
         }
     }
+
+    fn render_matrix(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let colors: Vec<ColorFormat> = palette.colors().into_iter().map(|c| c.into()).collect();
+        let header = |text: String| {
+            div()
+                .w(px(Self::CELL_SIZE))
+                .h(px(Self::CELL_SIZE))
+                .flex_center()
+                .text_size(px(8.))
+                .text_color(rgb(0x888888))
+                .child(text)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(1.))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(1.))
+                    .child(header("".into()))
+                    .children(SCALES.iter().map(|sc| header(sc.to_string()))),
+            )
+            .children(colors.iter().enumerate().map(|(i, fg)| {
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(px(1.))
+                    .child(header(SCALES[i].to_string()))
+                    .children(colors.iter().enumerate().map(|(j, bg)| {
+                        let result = WcagResult::new(*fg, *bg);
+                        let selected = self.selected == (i, j);
+                        div()
+                            .id(i * SCALES.len() + j)
+                            .w(px(Self::CELL_SIZE))
+                            .h(px(Self::CELL_SIZE))
+                            .flex_center()
+                            .rounded(px(3.))
+                            .cursor_pointer()
+                            .when(selected, |div| div.border_2().border_color(rgb(0xffffff)))
+                            .bg(bg.canonicalize())
+                            .text_color(fg.canonicalize())
+                            .text_size(px(8.))
+                            .child(result.label())
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.selected = (i, j);
+                                cx.notify();
+                            }))
+                    }))
+            }))
+    }
+
+    fn render_details(&self, cx: &mut ViewContext<Self>) -> Div {
+        let palette = *self.app_state.palette.read(cx);
+        let (i, j) = self.selected;
+        let fg = ColorFormat::from(palette.at_darkness(SCALES[i]));
+        let bg = ColorFormat::from(palette.at_darkness(SCALES[j]));
+        let result = WcagResult::new(fg, bg);
+        let apca = apca_contrast(fg, bg);
+        let check = |label: &'static str, pass: bool| {
+            div()
+                .flex()
+                .flex_row()
+                .justify_between()
+                .child(label)
+                .child(
+                    div()
+                        .text_color(if pass { rgb(0x22c55e) } else { rgb(0xef4444) })
+                        .child(if pass { "Pass" } else { "Fail" }),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(4.))
+            .text_size(px(13.))
+            .child(
+                div()
+                    .h(px(64.))
+                    .rounded(px(6.))
+                    .flex_center()
+                    .bg(bg.canonicalize())
+                    .text_color(fg.canonicalize())
+                    .text_size(px(20.))
+                    .child(format!("{} on {}", SCALES[i], SCALES[j])),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .child("Contrast ratio")
+                    .child(format!("{:.2}:1", result.ratio)),
+            )
+            .child(check("AA normal text", result.aa))
+            .child(check("AA large text", result.aa_large))
+            .child(check("AAA normal text", result.aaa))
+            .child(check("AAA large text", result.aaa_large))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .child("APCA")
+                    .child(format!("Lc {:.1}", apca)),
+            )
+    }
 }
 
 impl FocusableView for RightPanel {
@@ -23,8 +146,26 @@ impl Render for RightPanel {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         div()
             .w(rems(20.))
+            .min_w(rems(20.))
             .h_full()
-            .bg(rgb(0x448844))
-            .child("Left panel")
+            .bg(rgb(0x111111))
+            .text_color(rgb(0xcccccc))
+            .border_size(rems(1.), rems(0.8), rems(1.), rems(0.8))
+            .flex()
+            .flex_col()
+            .gap(rems(1.))
+            .child(
+                div()
+                    .text_size(rems(0.8))
+                    .child("Contrast (text \\ background)"),
+            )
+            .child(self.render_matrix(cx))
+            .child(self.render_details(cx))
+            .child(
+                div()
+                    .text_size(px(11.))
+                    .text_color(rgb(0x888888))
+                    .child("AAA ≥ 7:1, AA ≥ 4.5:1, AA18 ≥ 3:1 (large text only)"),
+            )
     }
 }
@@ -0,0 +1,155 @@
+use gpui::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextFieldEvent {
+    Changed(String),
+    Submit(String),
+    Cancel,
+}
+
+/// Single line text input. Only appends and deletes at the end, which is enough for names and
+/// color codes.
+pub struct TextField {
+    text: String,
+    placeholder: SharedString,
+    invalid: bool,
+    focus_handle: FocusHandle,
+}
+
+impl EventEmitter<TextFieldEvent> for TextField {}
+
+impl TextField {
+    pub fn new(
+        cx: &mut ViewContext<Self>,
+        text: impl Into<String>,
+        placeholder: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            placeholder: placeholder.into(),
+            invalid: false,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, cx: &mut ViewContext<Self>, text: impl Into<String>) {
+        self.text = text.into();
+        cx.notify();
+    }
+
+    /// Marks the content as invalid, drawing the field with a red border.
+    pub fn set_invalid(&mut self, cx: &mut ViewContext<Self>, invalid: bool) {
+        if self.invalid != invalid {
+            self.invalid = invalid;
+            cx.notify();
+        }
+    }
+
+    pub fn is_focused(&self, cx: &WindowContext) -> bool {
+        self.focus_handle.is_focused(cx)
+    }
+
+    fn handle_keydown(&mut self, ev: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let keystroke = &ev.keystroke;
+        let modifiers = keystroke.modifiers;
+        let changed = match keystroke.key.as_str() {
+            "enter" => {
+                cx.emit(TextFieldEvent::Submit(self.text.clone()));
+                false
+            }
+            "escape" => {
+                cx.emit(TextFieldEvent::Cancel);
+                cx.blur();
+                false
+            }
+            "backspace" if modifiers.command || modifiers.alt => {
+                let end = self.text.trim_end().rfind(' ').map_or(0, |i| i + 1);
+                self.text.truncate(end);
+                true
+            }
+            "backspace" => self.text.pop().is_some(),
+            "v" if modifiers.command || modifiers.control => match cx.read_from_clipboard() {
+                Some(item) => {
+                    self.text.push_str(item.text().trim());
+                    true
+                }
+                None => false,
+            },
+            _ if modifiers.command || modifiers.control => return,
+            "space" => {
+                self.text.push(' ');
+                true
+            }
+            key => match &keystroke.ime_key {
+                Some(s) => {
+                    self.text.push_str(s);
+                    true
+                }
+                None if key.chars().count() == 1 => {
+                    self.text.push_str(key);
+                    true
+                }
+                None => return,
+            },
+        };
+        cx.stop_propagation();
+        if changed {
+            cx.emit(TextFieldEvent::Changed(self.text.clone()));
+            cx.notify();
+        }
+    }
+}
+
+impl FocusableView for TextField {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TextField {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let focused = self.focus_handle.is_focused(cx);
+        let border_color = if self.invalid {
+            rgb(0xef4444)
+        } else if focused {
+            rgb(0x888888)
+        } else {
+            rgb(0x333333)
+        };
+
+        div()
+            .id("text-field")
+            .key_context("TextField")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::handle_keydown))
+            .on_click(cx.listener(|this, _, cx| cx.focus(&this.focus_handle)))
+            .w_full()
+            .h(px(28.))
+            .px(px(8.))
+            .rounded(px(4.))
+            .border_1()
+            .border_color(border_color)
+            .bg(rgb(0x111111))
+            .cursor_text()
+            .flex()
+            .flex_row()
+            .items_center()
+            .text_size(px(13.))
+            .map(|div0| {
+                if self.text.is_empty() && !focused {
+                    div0.text_color(rgb(0x666666))
+                        .child(self.placeholder.clone())
+                } else {
+                    div0.text_color(rgb(0xdddddd))
+                        .child(self.text.clone())
+                        .when(focused, |div0| {
+                            div0.child(div().w(px(1.)).h(px(16.)).bg(rgb(0xdddddd)))
+                        })
+                }
+            })
+    }
+}
@@ -1,26 +1,75 @@
 use super::*;
-use crate::{AppState, ColorFormat, ColorPalette, MainTab, KEY_SPACE};
+use crate::{
+    AppState, ColorFormat, ColorHistory, ColorLibrary, ColorPalette, ColorVision, DesignSystem,
+    MainTab, NewerVersion, RandomColor, RandomColors, RandomConfig, Redo, ResultExt, Undo,
+};
 use gpui::*;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 pub struct Workspace {
     app_state: Arc<AppState>,
     title_bar: View<TitleBar>,
     left_panel: View<LeftPanel>,
     central: View<Central>,
+    right_panel: View<RightPanel>,
     focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl Workspace {
-    pub fn new(cx: &mut ViewContext<Self>) -> Self {
-        let color = ColorFormat::random_hsl();
+    pub fn new(cx: &mut ViewContext<Self>, color: Option<ColorFormat>) -> Self {
+        let mut random = RandomColors::new(RandomConfig::default());
+        let color = color.unwrap_or_else(|| random.next(ColorFormat::Hsl(0., 0., 50.)));
+        let library_path = ColorLibrary::default_path();
+        let (library, library_path) = match library_path {
+            Some(path) => {
+                let (library, writable) = Self::load_library(&path);
+                (library, writable.then_some(path))
+            }
+            None => (ColorLibrary::default(), None),
+        };
+
+        let palette = ColorPalette::from(color.canonicalize());
 
         let app_state = Arc::new(AppState {
             color: cx.new_model(|cx| color),
-            palette: cx.new_model(|cx| ColorPalette::from(color.canonicalize())),
+            palette: cx.new_model(|cx| palette),
+            design_system: cx.new_model(|cx| DesignSystem::new(color, &palette)),
             main_tab: cx.new_model(|cx| MainTab::Home),
-            library: cx.new_model(|cx| None),
+            library: cx.new_model(|cx| Some(library)),
+            color_vision: cx.new_model(|cx| ColorVision::Normal),
+            history: cx.new_model(|cx| ColorHistory::new(color)),
+            random: cx.new_model(|cx| random),
         });
+        // undo and redo set the color to the current entry, which doesn't push it again
+        let history = app_state.history.clone();
+        let mut _subscriptions = vec![cx.observe(&app_state.color, move |_, color, cx| {
+            let color = *color.read(cx);
+            history.update(cx, |history, cx| {
+                history.push(color);
+                cx.notify();
+            });
+        })];
+        // every change of the main color goes with a change of the palette
+        let (color, design_system) = (app_state.color.clone(), app_state.design_system.clone());
+        _subscriptions.push(cx.observe(&app_state.palette, move |_, palette, cx| {
+            let (color, palette) = (*color.read(cx), *palette.read(cx));
+            design_system.update(cx, |design_system, cx| {
+                design_system.update_active(color, &palette);
+                cx.notify();
+            });
+        }));
+        if let Some(path) = library_path {
+            let writer = LibraryWriter::new(path);
+            _subscriptions.push(cx.observe(&app_state.library, move |_, library, cx| {
+                if let Some(library) = library.read(cx) {
+                    writer.save(cx, library.clone());
+                }
+            }));
+        }
+
         let weak_handle = cx.view().downgrade();
         let workspace = Self {
             app_state: app_state.clone(),
@@ -28,42 +77,176 @@ impl Workspace {
             left_panel: cx
                 .new_view(|cx| LeftPanel::new(cx, weak_handle.clone(), app_state.clone())),
             central: cx.new_view(|cx| Central::new(cx, weak_handle.clone(), app_state.clone())),
+            right_panel: cx.new_view(|cx| RightPanel::new(cx, app_state.clone())),
             focus_handle: cx.focus_handle(),
+            _subscriptions,
         };
 
         workspace
     }
 
+    // A library that can't be read is moved aside to a backup rather than being overwritten by
+    // the next save. One written by a newer version is left where it is, and never saved over:
+    // the returned flag tells whether changes may be written to `path`.
+    fn load_library(path: &Path) -> (ColorLibrary, bool) {
+        match ColorLibrary::load(path) {
+            Ok(Some(library)) => (library, true),
+            Ok(None) => (ColorLibrary::default(), true),
+            Err(err) if err.downcast_ref::<NewerVersion>().is_some() => {
+                tracing::error!(
+                    "color library left untouched, changes won't be saved: {:?}",
+                    err
+                );
+                (ColorLibrary::default(), false)
+            }
+            Err(err) => {
+                tracing::error!("failed to load color library: {:?}", err);
+                match ColorLibrary::move_aside(path) {
+                    Ok(backup) => {
+                        tracing::info!("unreadable library moved to {}", backup.display());
+                        (ColorLibrary::default(), true)
+                    }
+                    Err(err) => {
+                        tracing::error!("changes to the library won't be saved: {:?}", err);
+                        (ColorLibrary::default(), false)
+                    }
+                }
+            }
+        }
+    }
+
     fn handle_keydown(&mut self, ev: &KeyDownEvent, cx: &mut ViewContext<Self>) {
         if let KeyDownEvent {
             keystroke,
             is_held: false,
         } = ev
         {
-            if keystroke.modifiers == Modifiers::default() {
-                if keystroke.key == KEY_SPACE.key {
-                    self.new_random_color(cx);
-                    cx.refresh();
-                }
+            // text fields handle their own paste, so this only sees pastes outside of them
+            let modifiers = keystroke.modifiers;
+            if keystroke.key == "v" && (modifiers.command || modifiers.control) {
+                self.paste_color(cx);
             }
         }
     }
 
-    fn set_color(&mut self, cx: &mut ViewContext<Self>, new_color: Hsla) {
-        let new_palette = ColorPalette::from(new_color);
+    pub fn set_color(&mut self, cx: &mut ViewContext<Self>, new_color: Hsla) {
+        self.set_color_format(cx, ColorFormat::from(new_color));
+    }
+
+    /// Sets the color without converting it, keeping e.g. OKLCH colors in OKLCH.
+    pub fn set_color_format(&mut self, cx: &mut ViewContext<Self>, new_color: ColorFormat) {
+        self.app_state.color.update(cx, |color, cx| {
+            *color = new_color;
+            cx.notify();
+        });
+        self.app_state.palette.update(cx, |palette, cx| {
+            *palette = palette.with_base(new_color.canonicalize());
+            cx.notify();
+        });
+    }
+
+    fn paste_color(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(item) = cx.read_from_clipboard() else {
+            return;
+        };
+        match ColorFormat::parse(item.text().trim()) {
+            Ok(color) => self.set_color_format(cx, color),
+            Err(err) => tracing::info!("clipboard is not a color: {}", err),
+        }
+    }
+
+    /// Makes a palette of the design system the one being edited.
+    pub fn select_palette(&mut self, cx: &mut ViewContext<Self>, index: usize) {
+        let spec = self
+            .app_state
+            .design_system
+            .update(cx, |design_system, cx| {
+                design_system.set_active(index);
+                cx.notify();
+                design_system.active_palette().clone()
+            });
+        // undoing must not bring another palette's colors into this one
+        self.app_state.history.update(cx, |history, cx| {
+            history.reset(spec.color);
+            cx.notify();
+        });
         self.app_state.color.update(cx, |color, cx| {
-            *color = ColorFormat::from(new_color);
+            *color = spec.color;
             cx.notify();
         });
         self.app_state.palette.update(cx, |palette, cx| {
-            *palette = new_palette;
+            *palette = spec.palette();
+            cx.notify();
+        });
+    }
+
+    /// Replaces the design system, e.g. with one loaded from a project file.
+    pub fn set_design_system(&mut self, cx: &mut ViewContext<Self>, design_system: DesignSystem) {
+        self.app_state.design_system.update(cx, |x, cx| {
+            *x = design_system;
+            cx.notify();
+        });
+        self.select_palette(cx, 0);
+    }
+
+    fn undo(&mut self, _: &Undo, cx: &mut ViewContext<Self>) {
+        let color = self.app_state.history.update(cx, |history, cx| {
+            cx.notify();
+            history.undo()
+        });
+        if let Some(color) = color {
+            self.set_color_format(cx, color);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, cx: &mut ViewContext<Self>) {
+        let color = self.app_state.history.update(cx, |history, cx| {
             cx.notify();
+            history.redo()
         });
+        if let Some(color) = color {
+            self.set_color_format(cx, color);
+        }
     }
 
     fn new_random_color(&mut self, cx: &mut ViewContext<Self>) {
-        let new_color = ColorFormat::random_hsl();
-        self.set_color(cx, new_color.canonicalize());
+        let current = *self.app_state.color.read(cx);
+        let new_color = self
+            .app_state
+            .random
+            .update(cx, |random, _| random.next(current));
+        self.set_color_format(cx, new_color);
+    }
+}
+
+/// Saves the library off the main thread. Saves run one at a time, and one superseded by a newer
+/// save is skipped, so the file always ends up with the latest library.
+struct LibraryWriter {
+    path: Arc<PathBuf>,
+    latest: Arc<AtomicU64>, // number of the last save requested
+    lock: Arc<Mutex<()>>,
+}
+
+impl LibraryWriter {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path: Arc::new(path),
+            latest: Arc::new(AtomicU64::new(0)),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    fn save(&self, cx: &AppContext, library: ColorLibrary) {
+        let number = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        let (path, latest, lock) = (self.path.clone(), self.latest.clone(), self.lock.clone());
+        cx.background_executor()
+            .spawn(async move {
+                let _guard = lock.lock().unwrap_or_else(|x| x.into_inner());
+                if latest.load(Ordering::SeqCst) == number {
+                    library.save(&path).log_err();
+                }
+            })
+            .detach();
     }
 }
 
@@ -80,6 +263,9 @@ impl Render for Workspace {
             .key_context("Workspace")
             .track_focus(&self.focus_handle)
             .on_key_down(cx.listener(Self::handle_keydown))
+            .on_action(cx.listener(|this, _: &RandomColor, cx| this.new_random_color(cx)))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
             .size_full()
             .bg(rgba(0x000000))
             .flex()
@@ -91,7 +277,8 @@ impl Render for Workspace {
                     .flex()
                     .flex_row()
                     .child(self.left_panel.clone())
-                    .child(self.central.clone()),
+                    .child(self.central.clone())
+                    .child(self.right_panel.clone()),
             )
     }
 }